@@ -1,7 +1,7 @@
 use std::thread;
 
 use crossbeam_channel::unbounded;
-use immich::{upload::Uploaded, Album, Asset, Client};
+use immich::{upload::Uploaded, Asset, Client};
 
 fn main() {
     let mut args = std::env::args();
@@ -41,9 +41,8 @@ fn main() {
     });
 
     if let Some(album_name) = album {
-        let album = Album::get_or_create(&client, album_name).expect("Can't find or crate album");
         let result = client
-            .upload_to_album(5, assets, &album, Some(result_sender))
+            .upload_to_album_named(5, assets, album_name, Some(result_sender))
             .expect("Uploading to album works");
         println!("{} assets uploaded and moved", result.len());
     } else {
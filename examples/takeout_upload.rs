@@ -35,7 +35,7 @@ fn main() {
         let mut failure = 0;
         while let Ok(result) = result_receiver.recv() {
             match result.status() {
-                Status::Created => created += 1,
+                Status::Created | Status::Replaced => created += 1,
                 Status::Duplicate => duplicate += 1,
                 Status::Failure => failure += 1,
             };
@@ -1,16 +1,24 @@
-use crate::asset::AssetId;
+use crate::asset::{Asset, AssetId, RemoteAsset};
 use crate::User;
-use std::{slice::Iter, vec::IntoIter};
+use std::{
+    collections::{HashMap, HashSet},
+    io::Write,
+    slice::Iter,
+    vec::IntoIter,
+};
 
+use log::warn;
 use serde::{Deserialize, Serialize};
 
-use crate::api::requests::{AddToAlbum, MovedAsset};
+use crate::api::requests::{
+    AddToAlbum, AlbumAssets, DeleteAssets, DownloadArchive, MovedAsset, UpdateAlbum,
+};
 use crate::upload::{Status, Uploaded};
 use crate::utils::Id;
-use crate::{Client, ImmichError, ImmichResult};
+use crate::{Client, ImmichError, ImmichResult, SharedLink};
 
 #[allow(non_snake_case)]
-#[derive(Deserialize, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 /// Album on the remote Immich server
 ///
 /// # Examples
@@ -30,6 +38,10 @@ use crate::{Client, ImmichError, ImmichResult};
 /// ```
 pub struct Album {
     albumName: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default, skip_serializing)]
+    order: Option<AlbumOrder>,
     #[serde(skip_serializing)]
     assetCount: usize,
     #[serde(skip_serializing)]
@@ -64,29 +76,349 @@ impl Album {
     /// println!("{}: {}", album.name(), album.id());
     /// ```
     pub fn new(client: &Client, name: String) -> ImmichResult<Self> {
+        Self::new_with(client, name, String::new(), None)
+    }
+
+    /// Crates a new album with a description and sort order on the Immich server
+    ///
+    /// If `order` is `None`, the server's default sort order is used.
+    ///
+    /// # Errors
+    ///
+    /// If the sort order can't be set, the new album is deleted from the server again
+    /// and the error is returned.
+    ///
+    /// # Note
+    ///
+    /// The method does not check if an album with the same does exist already.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, AlbumOrder, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album = Album::new_with(
+    ///     &client,
+    ///     "My album".to_string(),
+    ///     "Our summer holiday".to_string(),
+    ///     Some(AlbumOrder::Asc),
+    /// ).unwrap();
+    /// println!("{}: {}", album.name(), album.description());
+    /// ```
+    pub fn new_with(
+        client: &Client,
+        name: String,
+        description: String,
+        order: Option<AlbumOrder>,
+    ) -> ImmichResult<Self> {
         let user = client.user()?;
         let album = Album {
             albumName: name,
+            description,
+            order: None,
             assetCount: 0,
             id: Id::default(),
             owner: user,
             shared: false,
         };
-        let response = client.post("/albums").send_json(album)?;
+        let mut album: Album = client.post_json("/albums", album)?;
+        if let Some(order) = order {
+            if let Err(err) = album.update(client, &UpdateAlbum::order(&order)) {
+                // don't leave behind a half-configured album
+                if let Err(delete_err) = album.delete(client) {
+                    warn!(
+                        "Failed to delete album {} after setting its order failed: {delete_err}",
+                        album.albumName
+                    );
+                }
+                return Err(err);
+            }
+            album.order = Some(order);
+        }
+        Ok(album)
+    }
 
-        if response.status() == 201 {
-            Ok(response.into_json()?)
-        } else {
-            Err(response.into())
+    /// Changes the description of the album on the Immich server
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// album.set_description(&client, "Imported from Google Photos").unwrap();
+    /// ```
+    pub fn set_description(&self, client: &Client, description: &str) -> ImmichResult<()> {
+        self.update(client, &UpdateAlbum::description(description))
+    }
+
+    /// Returns the ids of all images and videos in the album
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// for id in album.asset_ids(&client).unwrap() {
+    ///     println!("{id}");
+    /// }
+    /// ```
+    pub fn asset_ids(&self, client: &Client) -> ImmichResult<Vec<AssetId>> {
+        Ok(self
+            .assets(client)?
+            .into_iter()
+            .map(|asset| asset.id().clone())
+            .collect())
+    }
+
+    /// Returns true if the image or video with the given id is part of the album
+    ///
+    /// Every call fetches the album content from the server. Use [`Album::asset_ids`] to
+    /// check many assets at once.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, AssetId, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// let id = AssetId::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap();
+    /// if !album.contains(&client, &id).unwrap() {
+    ///     album.add_assets(&client, [id].into_iter()).unwrap();
+    /// }
+    /// ```
+    pub fn contains(&self, client: &Client, asset_id: &AssetId) -> ImmichResult<bool> {
+        Ok(self.asset_ids(client)?.contains(asset_id))
+    }
+
+    /// Returns all shared links of the album
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// for link in album.shared_links(&client).unwrap() {
+    ///     println!("{}: expires {:?}", link.key(), link.expires_at());
+    /// }
+    /// ```
+    pub fn shared_links(&self, client: &Client) -> ImmichResult<Vec<SharedLink>> {
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        // older servers ignore the `albumId` filter and return the links of all albums
+        let links: Vec<SharedLink> = Client::read_json(
+            client
+                .get("/shared-links")
+                .query("albumId", &self.id.to_string())
+                .call(),
+        )?;
+        Ok(links
+            .into_iter()
+            .filter(|link| link.album_id() == Some(&self.id))
+            .collect())
+    }
+
+    /// Returns all images and videos in the album
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// for asset in album.assets(&client).unwrap() {
+    ///     println!("{}: {}", asset.original_file_name(), asset.created_at());
+    /// }
+    /// ```
+    pub fn assets(&self, client: &Client) -> ImmichResult<Vec<RemoteAsset>> {
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidUrl(
+                "Album has an invalid Id".to_string(),
+            ));
+        }
+        let album: AlbumAssets = client.get_json(&format!("/albums/{}", self.id))?;
+        Ok(album.assets)
+    }
+
+    /// Downloads all images and videos of the album as a ZIP archive
+    ///
+    /// The archive is streamed to `writer`, so even large albums are not held in memory.
+    /// The original files are downloaded, e.g. to check that an import was complete.
+    /// Returns the number of bytes written.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// let file = File::create("/path/to/my-album.zip").unwrap();
+    /// let size = album.download_archive(&client, file).unwrap();
+    /// println!("Downloaded {size} bytes");
+    /// ```
+    pub fn download_archive<W: Write>(&self, client: &Client, mut writer: W) -> ImmichResult<u64> {
+        let ids = self.asset_ids(client)?;
+        let payload: DownloadArchive = ids.as_slice().into();
+        let response = Client::read_response(
+            client
+                .post("/download/archive")
+                .set("Accept", "application/octet-stream")
+                .send_json(payload),
+        )?;
+        Ok(std::io::copy(&mut response.into_reader(), &mut writer)?)
+    }
+
+    /// Deletes the album from the Immich server
+    ///
+    /// The images and videos of the album are not deleted.
+    pub fn delete(&self, client: &Client) -> ImmichResult<()> {
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidUrl(
+                "Album has an invalid Id".to_string(),
+            ));
+        }
+        Client::read_empty(client.delete(&format!("/albums/{}", self.id)).call())
+    }
+
+    /// Deletes the album and the images and videos that are in no other album
+    ///
+    /// This undoes an import into the album. To not lose any photos that live elsewhere,
+    /// images and videos are kept if they
+    /// - are in any other album of the user, including albums shared with the user
+    /// - are owned by another user than the album owner
+    /// - belong to an external library
+    ///
+    /// The images and videos are moved to the trash, or deleted permanently with `force`.
+    /// The album is only deleted if all images and videos were deleted successfully,
+    /// so that a failed call can be repeated.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// let deleted = album.delete_with_assets(&client, false).unwrap();
+    /// println!(
+    ///     "Trashed {} assets, kept {} assets",
+    ///     deleted.deleted().len(),
+    ///     deleted.kept().len()
+    /// );
+    /// ```
+    pub fn delete_with_assets(self, client: &Client, force: bool) -> ImmichResult<AlbumDeletion> {
+        let assets = self.assets(client)?;
+
+        // `GET /albums` only lists the albums of the user, albums of other users that are
+        // shared with the user are listed separately
+        let shared: Albums = client.get_json("/albums?shared=true")?;
+        let mut checked: HashSet<Id> = HashSet::from([self.id.clone()]);
+        let mut elsewhere: HashSet<AssetId> = HashSet::new();
+        for album in client.albums()?.into_iter().chain(shared) {
+            if !album.is_empty() && checked.insert(album.id.clone()) {
+                elsewhere.extend(album.asset_ids(client)?);
+            }
+        }
+
+        let (deleted, kept): (Vec<RemoteAsset>, Vec<RemoteAsset>) =
+            assets.into_iter().partition(|asset| {
+                !elsewhere.contains(asset.id())
+                    && asset.owner_id() == self.owner.id()
+                    && !asset.is_external()
+            });
+        let deleted: Vec<AssetId> = deleted
+            .into_iter()
+            .map(|asset| asset.id().clone())
+            .collect();
+        let kept: Vec<AssetId> = kept.into_iter().map(|asset| asset.id().clone()).collect();
+
+        if !deleted.is_empty() {
+            Client::read_empty(
+                client
+                    .delete("/assets")
+                    .send_json(DeleteAssets::new(deleted.iter().cloned(), force)),
+            )?;
+        }
+        self.delete(client)?;
+        Ok(AlbumDeletion { deleted, kept })
+    }
+
+    fn update(&self, client: &Client, payload: &UpdateAlbum) -> ImmichResult<()> {
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidUrl(
+                "Album has an invalid Id".to_string(),
+            ));
         }
+        Client::read_empty(
+            client
+                .patch(&format!("/albums/{}", self.id))
+                .send_json(payload),
+        )
     }
 
     /// Retrieves an album from the server or crates a new album
     ///
+    /// The result reports whether the album was newly created or already existed. Use
+    /// `.into()` if you only need the [`Album`] itself.
+    ///
     /// # Note
     ///
     /// If multiple albums with the same name exist, it will return the first result.
     ///
+    /// This method fetches all albums from the server on every call, unless the album cache
+    /// is enabled (see [`Client::with_album_cache`]).
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -98,21 +430,20 @@ impl Album {
     ///     "s3cr3tpassword"
     /// ).unwrap();
     ///
-    /// let album = Album::get_or_create(&client, "My album".to_string()).unwrap();
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
     /// println!("{}: {}", album.name(), album.id());
     ///
     /// let album2 = Album::get_or_create(&client, "My album".to_string()).unwrap();
-    /// assert_eq!(album.id(), album2.id());
+    /// assert!(!album2.created());
+    /// assert_eq!(album.id(), album2.album().id());
     /// ```
-    pub fn get_or_create(client: &Client, name: String) -> ImmichResult<Self> {
-        if let Some(album) = client
-            .albums()?
-            .into_iter()
-            .find(|album| album.name() == name)
-        {
-            Ok(album)
+    pub fn get_or_create(client: &Client, name: String) -> ImmichResult<GetOrCreate> {
+        if let Some(album) = client.find_album(&name)? {
+            Ok(GetOrCreate::Existing(album))
         } else {
-            Album::new(client, name)
+            let album = Album::new(client, name)?;
+            client.cache_album(&album);
+            Ok(GetOrCreate::Created(album))
         }
     }
 
@@ -121,11 +452,25 @@ impl Album {
         &self.albumName
     }
 
+    /// The description of the album
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// The sort order of the album's images and videos, if reported by the server
+    pub fn order(&self) -> Option<&AlbumOrder> {
+        self.order.as_ref()
+    }
+
     /// The number of images and videos in the album
     pub fn len(&self) -> usize {
         self.assetCount
     }
 
+    pub(crate) fn set_len(&mut self, len: usize) {
+        self.assetCount = len;
+    }
+
     /// Returns true if the album does not hold any images or videos
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -147,6 +492,38 @@ impl Album {
     }
 
     /// Add assets to the album
+    ///
+    /// Adding assets is idempotent: Assets that are already in the album are reported as
+    /// success with an [`AssetMoveError::Duplicate`](crate::AssetMoveError::Duplicate) error,
+    /// so that an interrupted call can simply be repeated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::NotFound`] if the album was deleted in the meantime
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, AssetId, Client, ImmichError};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let id = AssetId::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap();
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// match album.add_assets(&client, std::iter::once(id.clone())) {
+    ///     Err(ImmichError::NotFound(_)) => {
+    ///         let album = Album::new(&client, "My album".to_string()).unwrap();
+    ///         album.add_assets(&client, std::iter::once(id)).unwrap();
+    ///     }
+    ///     result => {
+    ///         result.unwrap();
+    ///     }
+    /// }
+    /// ```
     pub fn add_assets<I: Iterator<Item = AssetId>>(
         &self,
         client: &Client,
@@ -158,44 +535,87 @@ impl Album {
             ));
         }
         let payload: AddToAlbum = ids.into();
-        let response = client
-            .put(&format!("/albums/{}/assets", self.id))
-            .send_json(payload)?;
+        let mut moved: Vec<MovedAsset> =
+            client.put_json(&format!("/albums/{}/assets", self.id), payload)?;
+        moved.iter_mut().for_each(MovedAsset::accept_duplicate);
+        Ok(moved)
+    }
 
-        if response.status() == 200 {
-            Ok(response.into_json()?)
-        } else {
-            Err(ImmichError::Status(
-                response.status(),
-                response.into_string()?,
-            ))
-        }
+    /// Uploads a single asset and adds it to the album
+    ///
+    /// This is a simple alternative to [`Client::upload_to_album`] for scripts that process
+    /// assets one at a time. It does not spawn any threads.
+    ///
+    /// If the upload fails, the returned [`MovedAsset`] reports an
+    /// [`AssetMoveError::UploadFailed`](crate::AssetMoveError::UploadFailed) error and the
+    /// reason is logged as warning.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    ///
+    /// let mut asset: Asset = std::path::PathBuf::from("/path/to/image").try_into().unwrap();
+    /// let moved = album.upload_asset(&client, &mut asset).unwrap();
+    /// if let Some(id) = moved.id() {
+    ///     println!("{}: {}", id, moved.success());
+    /// }
+    /// ```
+    pub fn upload_asset(&self, client: &Client, asset: &mut Asset) -> ImmichResult<MovedAsset> {
+        let uploaded = match asset.upload(client) {
+            Ok(uploaded) => uploaded,
+            Err(err) => {
+                warn!("Failed to upload {}: {err}", asset.device_asset_id());
+                return Ok(MovedAsset::from_failed_upload());
+            }
+        };
+        let mut moved = self
+            .add_assets(client, std::iter::once(asset.id().clone()))?
+            .pop()
+            .ok_or(ImmichError::InvalidResponse)?;
+        moved.set_upload_status(uploaded.status().clone());
+        Ok(moved)
     }
 
     pub(crate) fn add_uploaded(
         &self,
         client: &Client,
-        results: Vec<Uploaded>,
+        results: &[Uploaded],
     ) -> ImmichResult<Vec<MovedAsset>> {
-        let iter_success = results.iter().filter_map(|uploaded| {
-            if uploaded.status() == &Status::Failure {
-                None
-            } else {
-                Some(uploaded.id().clone())
-            }
-        });
+        let iter_success = results.iter().filter_map(|uploaded| uploaded.id().cloned());
 
         let iter_failed = results.iter().filter_map(|uploaded| {
-            if uploaded.status() == &Status::Failure {
-                Some(MovedAsset::from_failed_upload(uploaded.id().clone()))
+            if uploaded.id().is_none() {
+                Some(MovedAsset::from_failed_upload())
             } else {
                 None
             }
         });
 
+        let statuses: HashMap<&AssetId, &Status> = results
+            .iter()
+            .filter_map(|uploaded| uploaded.id().map(|id| (id, uploaded.status())))
+            .collect();
+
+        if results.iter().all(|uploaded| uploaded.id().is_none()) {
+            return Ok(iter_failed.collect());
+        }
         // add all successfully uploaded assets to the album
         self.add_assets(client, iter_success)
             .map(|mut movedassets| {
+                for moved in movedassets.iter_mut() {
+                    if let Some(&status) = moved.id().and_then(|id| statuses.get(id)) {
+                        moved.set_upload_status(status.clone());
+                    }
+                }
                 // add all assets that failed to upload to the results
                 movedassets.extend(iter_failed);
                 movedassets
@@ -203,6 +623,70 @@ impl Album {
     }
 }
 
+/// Sort order of the images and videos in an [`Album`], by date
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum AlbumOrder {
+    /// Oldest first
+    #[serde(rename = "asc")]
+    Asc,
+    /// Newest first
+    #[serde(rename = "desc")]
+    Desc,
+}
+
+/// The result of [`Album::delete_with_assets`]
+#[derive(Clone, Debug)]
+pub struct AlbumDeletion {
+    deleted: Vec<AssetId>,
+    kept: Vec<AssetId>,
+}
+
+impl AlbumDeletion {
+    /// The ids of the images and videos that were deleted or moved to the trash
+    pub fn deleted(&self) -> &[AssetId] {
+        &self.deleted
+    }
+
+    /// The ids of the images and videos that were kept, e.g. because they are in other albums
+    pub fn kept(&self) -> &[AssetId] {
+        &self.kept
+    }
+}
+
+/// The result of [`Album::get_or_create`]
+///
+/// Reports whether the album was newly created or if an existing album was reused.
+pub enum GetOrCreate {
+    /// The album did not exist and was created
+    Created(Album),
+    /// The album already existed on the server
+    Existing(Album),
+}
+
+impl GetOrCreate {
+    /// The created or existing album
+    pub fn album(&self) -> &Album {
+        match self {
+            GetOrCreate::Created(album) => album,
+            GetOrCreate::Existing(album) => album,
+        }
+    }
+
+    /// Returns true if the album was newly created
+    pub fn created(&self) -> bool {
+        matches!(self, GetOrCreate::Created(_))
+    }
+}
+
+impl From<GetOrCreate> for Album {
+    fn from(value: GetOrCreate) -> Self {
+        match value {
+            GetOrCreate::Created(album) => album,
+            GetOrCreate::Existing(album) => album,
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(transparent)]
 /// Container that holds all or some [`Album`]s of the remote Immich server
@@ -235,6 +719,44 @@ impl Albums {
     pub fn is_empty(&self) -> bool {
         self.albums.is_empty()
     }
+
+    pub(crate) fn push(&mut self, album: Album) {
+        self.albums.push(album);
+    }
+
+    /// Returns an iterator over all albums
+    pub fn iter(&self) -> Iter<'_, Album> {
+        self.albums.iter()
+    }
+
+    /// Returns the first album with the given name
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let albums = client.albums().unwrap();
+    /// for name in ["Holiday", "Wedding"] {
+    ///     if let Some(album) = albums.find_by_name(name) {
+    ///         println!("{}: {}", album.name(), album.id());
+    ///     }
+    /// }
+    /// ```
+    pub fn find_by_name(&self, name: &str) -> Option<&Album> {
+        self.albums.iter().find(|album| album.name() == name)
+    }
+
+    /// Returns the album with the given id
+    pub fn get(&self, id: &Id) -> Option<&Album> {
+        self.albums.iter().find(|album| album.id() == id)
+    }
 }
 
 impl IntoIterator for Albums {
@@ -252,3 +774,352 @@ impl<'a> IntoIterator for &'a Albums {
         self.albums.iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{album_json, user_json};
+
+    fn albums_json() -> String {
+        let holiday = album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 12);
+        let wedding = r#"{
+                "albumName": "Wedding",
+                "description": "Our wedding",
+                "order": "asc",
+                "assetCount": 3,
+                "id": "41a3a296-7e86-4eb4-8e44-aead03344fc9",
+                "owner": {"id": "3fa85f64-5717-4562-b3fc-2c963f66afa6", "email": "a@b", "name": "A"},
+                "shared": true
+            }"#;
+        format!("[{holiday}, {wedding}]")
+    }
+
+    fn albums() -> Albums {
+        serde_json::from_str(&albums_json()).unwrap()
+    }
+
+    #[test]
+    fn find_albums() {
+        let albums = albums();
+        assert_eq!(albums.iter().count(), 2);
+        assert_eq!(albums.find_by_name("Wedding").unwrap().len(), 3);
+        assert!(albums.find_by_name("Birthday").is_none());
+
+        let wedding = albums.find_by_name("Wedding").unwrap();
+        assert_eq!(wedding.description(), "Our wedding");
+        assert_eq!(wedding.order(), Some(&AlbumOrder::Asc));
+        assert_eq!(albums.find_by_name("Holiday").unwrap().order(), None);
+
+        let id = Id::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap();
+        assert_eq!(albums.get(&id).unwrap().name(), "Holiday");
+    }
+
+    #[test]
+    fn album_contains() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("GET", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => (
+                200,
+                r#"{"assets": [{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "type": "IMAGE"}]}"#
+                    .to_string(),
+            ),
+            _ => (404, String::new()),
+        });
+        let album = albums().into_iter().next().unwrap();
+
+        let present = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+        assert!(album.contains(&client, &present).unwrap());
+        let missing = AssetId::try_from("3fa85f64-5717-4562-b3fc-2c963f66afa6").unwrap();
+        assert!(!album.contains(&client, &missing).unwrap());
+    }
+
+    #[test]
+    fn add_assets_twice() {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = calls.clone();
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("PUT", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets") => {
+                let response = if counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                    r#"[{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": true}]"#
+                } else {
+                    r#"[{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": false, "error": "duplicate"}]"#
+                };
+                (200, response.to_string())
+            }
+            _ => (404, String::new()),
+        });
+        let album = albums().into_iter().next().unwrap();
+        let id = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+
+        let first = album.add_assets(&client, [id.clone()].into_iter()).unwrap();
+        assert!(first[0].success());
+        assert!(first[0].error().is_none());
+
+        let second = album.add_assets(&client, [id].into_iter()).unwrap();
+        assert!(second[0].success());
+        assert_eq!(second[0].error(), &Some(crate::AssetMoveError::Duplicate));
+    }
+
+    #[test]
+    fn revoke_shared_links() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let deletes = AtomicUsize::new(0);
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("GET", "/shared-links?albumId=f0edb589-1312-4161-b41e-0a18f127b3dd") => (
+                200,
+                r#"[{
+                    "id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+                    "key": "k3y",
+                    "album": {"id": "f0edb589-1312-4161-b41e-0a18f127b3dd"}
+                }, {
+                    "id": "41a3a296-7e86-4eb4-8e44-aead03344fc9",
+                    "key": "0th3r",
+                    "description": "Other album",
+                    "album": {"id": "8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c"}
+                }]"#
+                .to_string(),
+            ),
+            ("DELETE", "/shared-links/6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e") => {
+                match deletes.fetch_add(1, Ordering::SeqCst) {
+                    0 => (404, r#"{"message": "Shared link not found"}"#.to_string()),
+                    1 => (
+                        400,
+                        r#"{"message": "Not found or no sharedLink.delete access"}"#.to_string(),
+                    ),
+                    _ => (400, r#"{"message": "Bad request"}"#.to_string()),
+                }
+            }
+            _ => (500, String::new()),
+        });
+        let album = albums().into_iter().next().unwrap();
+
+        let mut links = album.shared_links(&client).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].key(), "k3y");
+        assert_eq!(links[0].description(), "");
+        assert!(links[0].expires_at().is_none());
+        assert!(links.pop().unwrap().revoke(&client).is_ok());
+
+        // links that were deleted already are no error, other failures are
+        let link = || album.shared_links(&client).unwrap().pop().unwrap();
+        assert!(link().revoke(&client).is_ok());
+        assert!(matches!(
+            link().revoke(&client),
+            Err(ImmichError::Status(400, _))
+        ));
+    }
+
+    #[test]
+    fn new_album_with_order() {
+        use std::sync::{Arc, Mutex};
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let client = Client::mock(move |method, path| {
+            log.lock().unwrap().push(format!("{method} {path}"));
+            match (method, path) {
+                ("GET", "/users/me") => (200, user_json()),
+                ("POST", "/albums") => (
+                    201,
+                    album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 0),
+                ),
+                ("PATCH", _) => (500, r#"{"message": "Internal server error"}"#.to_string()),
+                ("DELETE", _) => (200, String::new()),
+                _ => (404, String::new()),
+            }
+        });
+
+        let album = Album::new_with(
+            &client,
+            "Holiday".to_string(),
+            String::new(),
+            Some(AlbumOrder::Asc),
+        );
+        assert!(matches!(album, Err(ImmichError::Status(500, _))));
+        assert!(requests
+            .lock()
+            .unwrap()
+            .contains(&"DELETE /albums/f0edb589-1312-4161-b41e-0a18f127b3dd".to_string()));
+    }
+
+    #[test]
+    fn download_archive() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let downloads = AtomicUsize::new(0);
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("GET", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => (
+                200,
+                r#"{"assets": [{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "type": "IMAGE"}]}"#
+                    .to_string(),
+            ),
+            ("POST", "/download/archive") => match downloads.fetch_add(1, Ordering::SeqCst) {
+                0 => (200, "PK\u{3}\u{4}zipped".to_string()),
+                _ => (500, r#"{"message": "Internal server error"}"#.to_string()),
+            },
+            _ => (404, String::new()),
+        });
+        let album = albums().into_iter().next().unwrap();
+
+        let mut archive = Vec::new();
+        let size = album.download_archive(&client, &mut archive).unwrap();
+        assert_eq!(size, 10);
+        assert_eq!(archive, b"PK\x03\x04zipped");
+
+        // the album lookup succeeds, but the download fails
+        assert!(matches!(
+            album.download_archive(&client, &mut Vec::new()),
+            Err(ImmichError::Status(500, body)) if body.contains("Internal server error")
+        ));
+    }
+
+    #[test]
+    fn delete_with_assets() {
+        use std::sync::{Arc, Mutex};
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let client = Client::mock(move |method, path| {
+            log.lock().unwrap().push(format!("{method} {path}"));
+            match (method, path) {
+                ("GET", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => (
+                    200,
+                    r#"{"assets": [
+                        {"id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e", "ownerId": "3fa85f64-5717-4562-b3fc-2c963f66afa6"},
+                        {"id": "8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c", "ownerId": "3fa85f64-5717-4562-b3fc-2c963f66afa6"},
+                        {"id": "2c1b0a9f-8e7d-4c6b-a5e4-3f2d1c0b9a8e", "ownerId": "9e8d7c6b-5a4f-4e3d-8c2b-1a0f9e8d7c6b"}
+                    ]}"#
+                    .to_string(),
+                ),
+                ("GET", "/albums") => (
+                    200,
+                    albums_json(),
+                ),
+                ("GET", "/albums?shared=true") => (200, "[]".to_string()),
+                ("GET", "/albums/41a3a296-7e86-4eb4-8e44-aead03344fc9") => (
+                    200,
+                    r#"{"assets": [{"id": "8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c"}]}"#.to_string(),
+                ),
+                ("DELETE", "/assets") => (204, String::new()),
+                ("DELETE", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => {
+                    (200, String::new())
+                }
+                _ => (500, String::new()),
+            }
+        });
+        let album = albums().into_iter().next().unwrap();
+
+        let deletion = album.delete_with_assets(&client, false).unwrap();
+        assert_eq!(deletion.deleted().len(), 1);
+        assert_eq!(
+            deletion.deleted()[0].to_string(),
+            "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e"
+        );
+        assert_eq!(deletion.kept().len(), 2);
+        assert!(requests.lock().unwrap().ends_with(&[
+            "DELETE /assets".to_string(),
+            "DELETE /albums/f0edb589-1312-4161-b41e-0a18f127b3dd".to_string()
+        ]));
+    }
+
+    #[test]
+    fn delete_with_assets_shared_album() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::{Arc, Mutex};
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let shared_fails = Arc::new(AtomicBool::new(false));
+        let fails = shared_fails.clone();
+        let client = Client::mock(move |method, path| {
+            log.lock().unwrap().push(format!("{method} {path}"));
+            match (method, path) {
+                ("GET", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => (
+                    200,
+                    r#"{"assets": [
+                        {"id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e", "ownerId": "3fa85f64-5717-4562-b3fc-2c963f66afa6"},
+                        {"id": "8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c", "ownerId": "3fa85f64-5717-4562-b3fc-2c963f66afa6"}
+                    ]}"#
+                    .to_string(),
+                ),
+                ("GET", "/albums") => (200, "[]".to_string()),
+                ("GET", "/albums?shared=true") if fails.load(Ordering::SeqCst) => {
+                    (500, String::new())
+                }
+                ("GET", "/albums?shared=true") => (
+                    200,
+                    r#"[{
+                        "albumName": "Partner",
+                        "assetCount": 1,
+                        "id": "5d1c2b3a-4e5f-4a6b-8c7d-9e0f1a2b3c4d",
+                        "owner": {"id": "9e8d7c6b-5a4f-4e3d-8c2b-1a0f9e8d7c6b", "email": "c@d", "name": "C"},
+                        "shared": true
+                    }]"#
+                    .to_string(),
+                ),
+                ("GET", "/albums/5d1c2b3a-4e5f-4a6b-8c7d-9e0f1a2b3c4d") => (
+                    200,
+                    r#"{"assets": [{"id": "8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c"}]}"#.to_string(),
+                ),
+                ("DELETE", "/assets") => (204, String::new()),
+                ("DELETE", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => {
+                    (200, String::new())
+                }
+                _ => (500, String::new()),
+            }
+        });
+
+        // the album can't be checked, so nothing is deleted
+        shared_fails.store(true, Ordering::SeqCst);
+        let album = albums().into_iter().next().unwrap();
+        assert!(album.delete_with_assets(&client, true).is_err());
+        assert!(!requests
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|request| request.starts_with("DELETE")));
+
+        shared_fails.store(false, Ordering::SeqCst);
+        let album = albums().into_iter().next().unwrap();
+        let deletion = album.delete_with_assets(&client, true).unwrap();
+        assert_eq!(
+            deletion.deleted()[0].to_string(),
+            "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e"
+        );
+        assert_eq!(
+            deletion.kept()[0].to_string(),
+            "8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c"
+        );
+    }
+
+    #[test]
+    fn deleted_album() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("PUT", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets") => (
+                400,
+                r#"{"message": "Not found or no album.update access", "statusCode": 400}"#
+                    .to_string(),
+            ),
+            ("PUT", _) => (400, r#"{"message": "ids must be an array"}"#.to_string()),
+            _ => (404, String::new()),
+        });
+        let id = AssetId::try_from("6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e").unwrap();
+        let mut albums = albums().into_iter();
+
+        let deleted = albums.next().unwrap();
+        assert!(matches!(
+            deleted.add_assets(&client, std::iter::once(id.clone())),
+            Err(ImmichError::NotFound(body)) if body.contains("album.update")
+        ));
+        assert!(matches!(
+            deleted.delete(&client),
+            Err(ImmichError::NotFound(_))
+        ));
+
+        let other = albums.next().unwrap();
+        assert!(matches!(
+            other.add_assets(&client, std::iter::once(id)),
+            Err(ImmichError::Status(400, _))
+        ));
+    }
+}
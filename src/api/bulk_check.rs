@@ -1,7 +1,9 @@
-use crate::asset::AssetRemoteStatus;
+use crate::asset::{AssetId, AssetRemoteStatus};
+use crate::upload::Uploaded;
 use crate::utils::Id;
 use crate::ImmichError;
 use crate::{asset::Asset, Client, ImmichResult};
+use crossbeam_channel::Sender;
 use std::iter::zip;
 
 use serde::Serialize;
@@ -45,10 +47,13 @@ impl IntoIterator for BulkCheckResults {
     }
 }
 
+#[allow(non_snake_case)]
 #[derive(Deserialize)]
 struct BulkCheckResult {
     pub id: Id,
     pub action: BulkCheckAction,
+    #[serde(default)]
+    pub assetId: Option<AssetId>,
 }
 
 impl BulkCheckResult {
@@ -63,21 +68,12 @@ struct BulkCheckRequest {
     checksum: String,
 }
 
-impl From<&Asset> for BulkCheckRequest {
-    fn from(asset: &Asset) -> Self {
-        Self {
-            id: asset.id().to_string(),
-            checksum: asset.checksum(),
-        }
-    }
-}
-
-impl From<Asset> for BulkCheckRequest {
-    fn from(asset: Asset) -> Self {
-        Self {
-            checksum: asset.checksum(),
-            id: asset.id().to_string(),
-        }
+impl BulkCheckRequest {
+    fn new(id: String, asset: &Asset) -> ImmichResult<Self> {
+        Ok(Self {
+            id,
+            checksum: asset.checksum()?,
+        })
     }
 }
 
@@ -90,17 +86,11 @@ impl BulkUploadCheck {
         client: &Client,
         assets: &mut I,
     ) -> ImmichResult<()> {
-        let data: Vec<BulkCheckRequest> = assets.map(BulkCheckRequest::from).collect();
-        let response = client.post(BulkUploadCheck::URL).send_json(data)?;
-
-        if response.status() != 200 {
-            return Err(ImmichError::Status(
-                response.status(),
-                response.status_text().to_string(),
-            ));
-        }
-
-        let results: BulkCheckResults = response.into_json()?;
+        let data = assets
+            .map(|asset| BulkCheckRequest::new(asset.id().to_string(), &asset))
+            .collect::<ImmichResult<Vec<_>>>()?;
+        let results: BulkCheckResults =
+            Client::read_json(client.post(BulkUploadCheck::URL).send_json(data))?;
 
         if assets.len() != results.len() {
             return Err(ImmichError::InvalidResponse);
@@ -113,4 +103,105 @@ impl BulkUploadCheck {
         }
         Ok(())
     }
+
+    /// Checks which of the assets are already present on the server
+    ///
+    /// Updates the [`Asset::remote_status`] of every asset. Assets that are present
+    /// on the server also receive the id of the existing remote asset.
+    pub fn check(client: &Client, assets: &mut [Asset]) -> ImmichResult<()> {
+        let data = assets
+            .iter()
+            .enumerate()
+            .map(|(idx, asset)| BulkCheckRequest::new(idx.to_string(), asset))
+            .collect::<ImmichResult<Vec<_>>>()?;
+        let results: BulkCheckResults =
+            Client::read_json(client.post(BulkUploadCheck::URL).send_json(data))?;
+
+        if assets.len() != results.len() {
+            return Err(ImmichError::InvalidResponse);
+        }
+
+        for (idx, (asset, result)) in zip(assets.iter_mut(), results).enumerate() {
+            if result.id() != idx.to_string().as_str() {
+                return Err(ImmichError::InvalidResponse);
+            }
+            match (result.action, result.assetId) {
+                (BulkCheckAction::Accept, _) => {
+                    *asset.remote_status_mut() = AssetRemoteStatus::Absent;
+                }
+                (BulkCheckAction::Reject, Some(id)) => {
+                    *asset.remote_status_mut() = AssetRemoteStatus::Present;
+                    *asset.id_mut() = id;
+                }
+                // rejected for other reasons, e.g. an unsupported format
+                (BulkCheckAction::Reject, None) => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iterator adapter that skips all assets that are already present on the server
+///
+/// The assets are checked in batches of `batch_size`, only their checksums are sent to
+/// the server. The assets of a batch are read ahead and held in memory until they are
+/// passed on, so the batch size should not exceed the upload buffer of the client
+/// (see [`Client::with_upload_buffer`]).
+///
+/// Every skipped asset is reported as a [`Status::Duplicate`](crate::upload::Status::Duplicate)
+/// to the `skipped` channel. If a batch cannot be checked, all of its assets are passed on.
+pub(crate) struct SkipPresent<'a, I> {
+    client: &'a Client,
+    assets: I,
+    batch_size: usize,
+    batch: IntoIter<Asset>,
+    skipped: Sender<Uploaded>,
+}
+
+impl<'a, I: Iterator<Item = Asset>> SkipPresent<'a, I> {
+    pub fn new(
+        client: &'a Client,
+        assets: I,
+        batch_size: usize,
+        skipped: Sender<Uploaded>,
+    ) -> Self {
+        Self {
+            client,
+            assets,
+            batch_size: batch_size.max(1),
+            batch: Vec::new().into_iter(),
+            skipped,
+        }
+    }
+
+    fn next_batch(&mut self) -> Option<()> {
+        let mut batch: Vec<Asset> = self.assets.by_ref().take(self.batch_size).collect();
+        if batch.is_empty() {
+            return None;
+        }
+        let _ = BulkUploadCheck::check(self.client, &mut batch);
+        self.batch = batch.into_iter();
+        Some(())
+    }
+}
+
+impl<I: Iterator<Item = Asset>> Iterator for SkipPresent<'_, I> {
+    type Item = Asset;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.batch.next() {
+                Some(asset) => {
+                    if let AssetRemoteStatus::Present = asset.remote_status() {
+                        let _ = self.skipped.send(Uploaded::from_duplicate(
+                            asset.device_asset_id(),
+                            asset.id().clone(),
+                        ));
+                    } else {
+                        return Some(asset);
+                    }
+                }
+                None => self.next_batch()?,
+            }
+        }
+    }
 }
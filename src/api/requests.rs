@@ -1,45 +1,165 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    asset::{Asset, AssetId},
-    utils::Id,
+    album::AlbumOrder,
+    asset::{Asset, AssetId, RemoteAsset},
+    upload::Status,
+    utils::{serialize_timestamp, DateTime, Id},
 };
 
 #[derive(Serialize)]
-pub(crate) struct BulkCheck {
-    id: String,
-    checksum: String,
+pub(crate) struct AddToAlbum {
+    ids: Vec<Id>,
+}
+
+impl<I: Iterator<Item = AssetId>> From<I> for AddToAlbum {
+    fn from(ids: I) -> Self {
+        Self { ids: ids.collect() }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct AssetIds {
+    ids: Vec<Id>,
+}
+
+impl<I: Iterator<Item = AssetId>> From<I> for AssetIds {
+    fn from(ids: I) -> Self {
+        Self { ids: ids.collect() }
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+pub(crate) struct CreateStack<'a> {
+    assetIds: &'a [AssetId],
+}
+
+impl<'a> From<&'a [AssetId]> for CreateStack<'a> {
+    fn from(ids: &'a [AssetId]) -> Self {
+        Self { assetIds: ids }
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+pub(crate) struct DownloadArchive<'a> {
+    assetIds: &'a [AssetId],
 }
 
-impl From<&Asset> for BulkCheck {
-    fn from(asset: &Asset) -> Self {
+impl<'a> From<&'a [AssetId]> for DownloadArchive<'a> {
+    fn from(ids: &'a [AssetId]) -> Self {
+        Self { assetIds: ids }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct DeleteAssets {
+    ids: Vec<Id>,
+    force: bool,
+}
+
+impl DeleteAssets {
+    pub fn new<I: Iterator<Item = AssetId>>(ids: I, force: bool) -> Self {
         Self {
-            id: asset.id().to_string(),
-            checksum: asset.checksum(),
+            ids: ids.collect(),
+            force,
         }
     }
 }
 
-impl From<Asset> for BulkCheck {
-    fn from(asset: Asset) -> Self {
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+pub(crate) struct UpdateAsset<'a> {
+    #[serde(serialize_with = "serialize_timestamp")]
+    dateTimeOriginal: &'a DateTime,
+}
+
+impl<'a> UpdateAsset<'a> {
+    pub fn date_time_original(date: &'a DateTime) -> Self {
         Self {
-            checksum: asset.checksum(),
-            id: asset.id().to_string(),
+            dateTimeOriginal: date,
         }
     }
 }
 
+#[allow(non_snake_case)]
 #[derive(Serialize)]
-pub(crate) struct AddToAlbum {
-    ids: Vec<Id>,
+pub(crate) struct UpdateAssets<'a> {
+    ids: &'a [Id],
+    isArchived: bool,
 }
 
-impl<I: Iterator<Item = AssetId>> From<I> for AddToAlbum {
-    fn from(ids: I) -> Self {
-        Self { ids: ids.collect() }
+impl<'a> UpdateAssets<'a> {
+    pub fn archived(ids: &'a [Id], archived: bool) -> Self {
+        Self {
+            ids,
+            isArchived: archived,
+        }
     }
 }
 
+#[derive(Serialize)]
+pub(crate) struct UpdateAlbum<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    order: Option<&'a AlbumOrder>,
+}
+
+impl<'a> UpdateAlbum<'a> {
+    pub fn description(description: &'a str) -> Self {
+        Self {
+            description: Some(description),
+            order: None,
+        }
+    }
+
+    pub fn order(order: &'a AlbumOrder) -> Self {
+        Self {
+            description: None,
+            order: Some(order),
+        }
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Serialize)]
+pub(crate) struct SearchTrashed {
+    withDeleted: bool,
+    trashedAfter: &'static str,
+    page: usize,
+    size: usize,
+}
+
+impl SearchTrashed {
+    pub fn page(page: usize) -> Self {
+        Self {
+            withDeleted: true,
+            trashedAfter: "1970-01-01T00:00:00.000Z",
+            page,
+            size: 250,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct SearchResponse {
+    pub assets: SearchAssets,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+pub(crate) struct SearchAssets {
+    pub items: Vec<Asset>,
+    pub nextPage: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct AlbumAssets {
+    pub assets: Vec<RemoteAsset>,
+}
+
 /// Types of errors that can occur while trying to assign an asset to an album
 #[derive(Debug, Deserialize, PartialEq, Eq)]
 pub enum AssetMoveError {
@@ -58,8 +178,13 @@ pub enum AssetMoveError {
 #[derive(Deserialize)]
 pub struct MovedAsset {
     error: Option<AssetMoveError>,
-    id: AssetId,
+    #[serde(default)]
+    id: Option<AssetId>,
     success: bool,
+    #[serde(skip)]
+    upload_status: Option<Status>,
+    #[serde(skip)]
+    album_name: Option<String>,
 }
 
 impl MovedAsset {
@@ -67,35 +192,235 @@ impl MovedAsset {
         if success {
             Self {
                 error: None,
-                id,
+                id: Some(id),
                 success,
+                upload_status: None,
+                album_name: None,
             }
         } else {
             Self {
                 error: Some(AssetMoveError::Unknown),
-                id,
+                id: Some(id),
                 success,
+                upload_status: None,
+                album_name: None,
             }
         }
     }
 
-    pub(crate) fn from_failed_upload(id: AssetId) -> Self {
+    /// The asset was not uploaded, so it has no id on the server
+    pub(crate) fn from_failed_upload() -> Self {
         Self {
             error: Some(AssetMoveError::UploadFailed),
-            id,
+            id: None,
             success: false,
+            upload_status: Some(Status::Failure),
+            album_name: None,
+        }
+    }
+
+    /// The asset is already part of the album
+    pub(crate) fn from_duplicate(id: AssetId) -> Self {
+        Self {
+            error: Some(AssetMoveError::Duplicate),
+            id: Some(id),
+            success: true,
+            upload_status: None,
+            album_name: None,
         }
     }
 
+    /// Reports assets that were already part of the album as success
+    ///
+    /// This way, adding the same assets to an album again does not report failures.
+    pub(crate) fn accept_duplicate(&mut self) {
+        if self.error == Some(AssetMoveError::Duplicate) {
+            self.success = true;
+        }
+    }
+
+    pub(crate) fn set_upload_status(&mut self, status: Status) {
+        self.upload_status = Some(status)
+    }
+
+    pub(crate) fn set_album_name(&mut self, name: &str) {
+        self.album_name = Some(name.to_string())
+    }
+
     pub fn error(&self) -> &Option<AssetMoveError> {
         &self.error
     }
 
-    pub fn id(&self) -> &AssetId {
-        &self.id
+    /// The id of the asset on the server
+    ///
+    /// Returns `None` if the asset was never uploaded, see [`AssetMoveError::UploadFailed`].
+    pub fn id(&self) -> Option<&AssetId> {
+        self.id.as_ref()
     }
 
+    /// Returns true if the asset is part of the album
+    ///
+    /// Assets that were already in the album before are reported as success, too.
+    /// They come with an [`AssetMoveError::Duplicate`] error to tell them apart.
     pub fn success(&self) -> bool {
         self.success
     }
+
+    /// The [`Status`] of the upload that preceded adding the asset to the album
+    ///
+    /// Distinguishes assets that were freshly uploaded ([`Status::Created`]) from assets
+    /// that already existed on the server ([`Status::Duplicate`]).
+    /// Returns `None` if the asset was added to the album without being uploaded first,
+    /// e.g. via [`Album::add_assets`](crate::Album::add_assets).
+    pub fn upload_status(&self) -> Option<&Status> {
+        self.upload_status.as_ref()
+    }
+
+    /// The name of the album the asset was added to
+    ///
+    /// Only set by [`Client::upload_with_albums`](crate::Client::upload_with_albums),
+    /// which adds assets to several albums at once.
+    pub fn album_name(&self) -> Option<&str> {
+        self.album_name.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AssetType;
+
+    #[test]
+    fn moved_asset_upload_status() {
+        let mut moved: MovedAsset = serde_json::from_str(
+            r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "success": true}"#,
+        )
+        .unwrap();
+        assert!(moved.success());
+        assert!(moved.upload_status().is_none());
+
+        moved.set_upload_status(Status::Duplicate);
+        assert_eq!(moved.upload_status(), Some(&Status::Duplicate));
+
+        let failed = MovedAsset::from_failed_upload();
+        assert_eq!(failed.upload_status(), Some(&Status::Failure));
+        assert!(failed.id().is_none());
+    }
+
+    #[test]
+    fn deserialize_album_assets() {
+        let album: AlbumAssets = serde_json::from_str(
+            r#"{
+                "albumName": "Holiday",
+                "id": "3fa85f64-5717-4562-b3fc-2c963f66afa6",
+                "assetCount": 1,
+                "assets": [
+                    {"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "type": "IMAGE"},
+                    {
+                        "id": "41a3a296-7e86-4eb4-8e44-aead03344fc9",
+                        "deviceAssetId": "VID_1.mp4",
+                        "deviceId": "immich-rs",
+                        "ownerId": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+                        "owner": {
+                            "id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+                            "email": "email@somewhere",
+                            "name": "Username",
+                            "profileImagePath": ""
+                        },
+                        "type": "VIDEO",
+                        "libraryId": "0d1173e3-4d80-4d76-b41e-57d56de21125",
+                        "isExternal": true,
+                        "isOffline": true,
+                        "originalFileName": "VID_1.mp4",
+                        "fileCreatedAt": "2023-02-11T10:04:12.000Z",
+                        "fileModifiedAt": "2023-02-11T10:04:13.000Z",
+                        "updatedAt": "2025-01-28T05:42:36.123Z",
+                        "isFavorite": false,
+                        "isArchived": true,
+                        "isTrashed": false,
+                        "duration": "0:00:12.000000",
+                        "checksum": "TLa/w9Q2xpWyMNUMtasdea7zL24="
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(album.assets.len(), 2);
+        assert_eq!(
+            album.assets[0].id().to_string(),
+            "f0edb589-1312-4161-b41e-0a18f127b3dd"
+        );
+        assert_eq!(album.assets[0].asset_type(), &AssetType::Image);
+
+        let video = &album.assets[1];
+        assert_eq!(video.asset_type(), &AssetType::Video);
+        assert_eq!(video.original_file_name(), "VID_1.mp4");
+        assert_eq!(video.owner().unwrap().name(), "Username");
+        assert_eq!(video.owner_id(), "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e");
+        assert_eq!(video.modified_at().to_string(), "2023-02-11T10:04:13.000Z");
+        assert!(video.archived());
+        assert!(video.is_external());
+        assert!(video.offline());
+        assert!(!album.assets[0].is_external());
+    }
+
+    #[test]
+    fn deserialize_search_response() {
+        let response: SearchResponse = serde_json::from_str(
+            r#"{
+                "albums": {"total": 0, "count": 0, "items": [], "facets": []},
+                "assets": {
+                    "total": 1,
+                    "count": 1,
+                    "items": [{
+                        "id": "f0edb589-1312-4161-b41e-0a18f127b3dd",
+                        "deviceAssetId": "IMG_1.jpg",
+                        "deviceId": "Immich-0.1 (Rust Client)",
+                        "fileCreatedAt": "2013-06-09T07:14:29.000Z",
+                        "fileModifiedAt": "2013-06-09T07:14:29.000Z",
+                        "type": "IMAGE",
+                        "libraryId": "0d1173e3-4d80-4d76-b41e-57d56de21125",
+                        "isTrashed": true
+                    }],
+                    "facets": [],
+                    "nextPage": null
+                }
+            }"#,
+        )
+        .unwrap();
+        assert!(response.assets.nextPage.is_none());
+        let asset = &response.assets.items[0];
+        assert_eq!(asset.device_asset_id(), "IMG_1.jpg");
+        assert_eq!(asset.created_at().to_string(), "2013-06-09T07:14:29.000Z");
+        assert!(asset.asset_data().is_empty());
+        assert!(!asset.is_external());
+    }
+
+    #[test]
+    fn external_assets_are_read_only() {
+        let asset: Asset = serde_json::from_str(
+            r#"{
+                "id": "f0edb589-1312-4161-b41e-0a18f127b3dd",
+                "deviceAssetId": "IMG_1.jpg",
+                "deviceId": "Library Import",
+                "fileCreatedAt": "2013-06-09T07:14:29.000Z",
+                "fileModifiedAt": "2013-06-09T07:14:29.000Z",
+                "type": "IMAGE",
+                "libraryId": "0d1173e3-4d80-4d76-b41e-57d56de21125",
+                "isExternal": true
+            }"#,
+        )
+        .unwrap();
+        assert!(asset.is_external());
+
+        let client = crate::Client::mock(|_, _| (204, String::new()));
+        assert!(matches!(
+            asset.trash(&client),
+            Err(crate::ImmichError::ExternalAsset(_))
+        ));
+        assert!(matches!(
+            asset.sync_timestamps(&client),
+            Err(crate::ImmichError::ExternalAsset(_))
+        ));
+    }
 }
@@ -1,13 +1,20 @@
+use std::collections::hash_map::{Entry, RandomState};
+use std::collections::HashMap;
+use std::convert::Infallible;
 use std::fmt::Display;
+use std::hash::{BuildHasher, Hasher};
+use std::io::Read;
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
 use crossbeam_channel::{bounded, unbounded, Receiver, Sender};
+use log::{info, warn};
 use serde::Deserialize;
-use ureq::Response;
+use ureq::{Request, Response};
 
 use crate::client::ImmichClient;
 use crate::{multipart::MultipartBuilder, Asset, Client, ImmichResult};
-use crate::{AssetId, ImmichError};
+use crate::{AssetId, AssetType, DateTime, ImmichError};
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 /// Response status of an asset upload
@@ -16,6 +23,8 @@ pub enum Status {
     Created,
     #[serde(rename(deserialize = "duplicate"))]
     Duplicate,
+    #[serde(rename(deserialize = "replaced"))]
+    Replaced,
     Failure,
 }
 
@@ -27,6 +36,7 @@ impl Display for Status {
             match self {
                 Status::Created => "Created",
                 Status::Duplicate => "Duplicate",
+                Status::Replaced => "Replaced",
                 Status::Failure => "Failure",
             }
         )
@@ -35,25 +45,64 @@ impl Display for Status {
 
 #[derive(Clone, Debug, Deserialize)]
 /// Response of the Immich server for an uploaded asset
+///
+/// The server only responds with the id and the status of the asset. The type, filename
+/// and timestamps are those of the uploaded [`Asset`].
 pub struct Uploaded {
     status: Status,
-    id: AssetId,
+    #[serde(default)]
+    id: Option<AssetId>,
     #[serde(default)]
     device_asset_id: String,
+    #[serde(skip)]
+    error: Option<String>,
+    #[serde(skip)]
+    asset_type: Option<AssetType>,
+    #[serde(skip)]
+    original_file_name: Option<String>,
+    #[serde(skip)]
+    file_created_at: Option<DateTime>,
+    #[serde(skip)]
+    file_modified_at: Option<DateTime>,
 }
 
 impl Uploaded {
-    pub(crate) fn from_failure(device_asset_id: &str) -> Self {
+    pub(crate) fn from_failure<E: Display>(device_asset_id: &str, error: &E) -> Self {
         Self {
             status: Status::Failure,
-            id: AssetId::default(),
+            id: None,
+            device_asset_id: String::from(device_asset_id),
+            error: Some(error.to_string()),
+            asset_type: None,
+            original_file_name: None,
+            file_created_at: None,
+            file_modified_at: None,
+        }
+    }
+
+    pub(crate) fn from_duplicate(device_asset_id: &str, id: AssetId) -> Self {
+        Self {
+            status: Status::Duplicate,
+            id: Some(id),
             device_asset_id: String::from(device_asset_id),
+            error: None,
+            asset_type: None,
+            original_file_name: None,
+            file_created_at: None,
+            file_modified_at: None,
         }
     }
 
     /// Returns the id of the uploaded/checked [`Asset`]
-    pub fn id(&self) -> &AssetId {
-        &self.id
+    ///
+    /// Failed uploads don't have an id on the server and return `None`
+    pub fn id(&self) -> Option<&AssetId> {
+        self.id.as_ref()
+    }
+
+    /// Returns true if the asset already existed on the server and was not uploaded again
+    pub fn existed(&self) -> bool {
+        self.status == Status::Duplicate
     }
 
     /// Returns the client-side id of the uploaded/checked [`Asset`]
@@ -66,14 +115,136 @@ impl Uploaded {
     /// This can be either
     /// - `created`: The asset was uploaded successfully
     /// - `duplicate`: The asset did already exist on the Immich server and was not uploaded
+    /// - `replaced`: The file of an existing asset was replaced, see [`Asset::replace`]
     /// - `failure`: : The upload of the asset failed
     pub fn status(&self) -> &Status {
         &self.status
     }
 
-    pub(crate) fn device_asset_id_mut(&mut self) -> &mut String {
-        self.device_asset_id.clear();
-        &mut self.device_asset_id
+    /// Returns the reason why the upload failed
+    ///
+    /// This is only set for a [`Status::Failure`], e.g. if the server rejected an
+    /// unsupported file type.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// The [`AssetType`] of the uploaded asset
+    ///
+    /// Failed and skipped uploads return `None`.
+    pub fn asset_type(&self) -> Option<&AssetType> {
+        self.asset_type.as_ref()
+    }
+
+    /// The filename of the uploaded asset, see [`Asset::filename`]
+    ///
+    /// Failed and skipped uploads return `None`.
+    pub fn original_file_name(&self) -> Option<&str> {
+        self.original_file_name.as_deref()
+    }
+
+    /// The creation timestamp of the uploaded asset
+    ///
+    /// Failed and skipped uploads return `None`.
+    pub fn file_created_at(&self) -> Option<&DateTime> {
+        self.file_created_at.as_ref()
+    }
+
+    /// The modification timestamp of the uploaded asset
+    ///
+    /// Failed and skipped uploads return `None`.
+    pub fn file_modified_at(&self) -> Option<&DateTime> {
+        self.file_modified_at.as_ref()
+    }
+
+    /// Adds the details of the uploaded asset to the response of the server
+    pub(crate) fn with_asset(self, asset: &Asset) -> Self {
+        Self {
+            device_asset_id: asset.device_asset_id().to_string(),
+            asset_type: Some(asset.asset_type().clone()),
+            original_file_name: Some(asset.filename().to_string()),
+            file_created_at: Some(asset.created_at().clone()),
+            file_modified_at: Some(asset.modified_at().clone()),
+            ..self
+        }
+    }
+}
+
+/// Summary of many uploads, e.g. from [`Client::upload_summarized`]
+///
+/// # Examples
+///
+/// ```
+/// use immich::upload::{Uploaded, UploadSummary};
+///
+/// let results: Vec<Uploaded> = Vec::new();
+/// let summary = UploadSummary::from(results);
+/// assert_eq!(summary.total(), 0);
+/// assert_eq!(summary.to_string(), "Created: 0 | Duplicate: 0 | Failure: 0 | Total: 0");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct UploadSummary {
+    created: usize,
+    duplicate: usize,
+    failure: usize,
+    total: usize,
+    ids: Vec<AssetId>,
+}
+
+impl UploadSummary {
+    /// The number of newly created assets
+    ///
+    /// This includes the assets whose file was replaced ([`Status::Replaced`]).
+    pub fn created(&self) -> usize {
+        self.created
+    }
+
+    /// The number of assets that were already present on the server
+    pub fn duplicate(&self) -> usize {
+        self.duplicate
+    }
+
+    /// The number of assets that failed to upload
+    pub fn failure(&self) -> usize {
+        self.failure
+    }
+
+    /// The total number of processed assets
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// The ids of all assets that are on the server, i.e. were not a [`Status::Failure`]
+    pub fn ids(&self) -> &[AssetId] {
+        &self.ids
+    }
+}
+
+impl From<Vec<Uploaded>> for UploadSummary {
+    fn from(results: Vec<Uploaded>) -> Self {
+        let mut summary = UploadSummary {
+            total: results.len(),
+            ..Default::default()
+        };
+        for uploaded in results {
+            match uploaded.status {
+                Status::Created | Status::Replaced => summary.created += 1,
+                Status::Duplicate => summary.duplicate += 1,
+                Status::Failure => summary.failure += 1,
+            }
+            summary.ids.extend(uploaded.id);
+        }
+        summary
+    }
+}
+
+impl Display for UploadSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Created: {} | Duplicate: {} | Failure: {} | Total: {}",
+            self.created, self.duplicate, self.failure, self.total
+        )
     }
 }
 
@@ -81,31 +252,100 @@ pub(crate) struct Upload {}
 
 impl Upload {
     const URL: &str = "/assets";
+    /// How often an upload is retried if the server responds with `429 Too Many Requests`
+    const MAX_RETRIES: u32 = 5;
+    /// Upper limit of the delay between retries, also if the server sends a longer `Retry-After`
+    const MAX_BACKOFF: Duration = Duration::from_secs(60);
 
-    pub fn post(client: &Client, asset: &Asset) -> ImmichResult<Response> {
-        let (content_type, data) = Upload::format_data(asset)?;
+    /// Uploads the asset, pausing and retrying while the server is rate limiting
+    pub fn post(client: &Client, asset: &Asset, checksum: &str) -> ImmichResult<Response> {
+        Upload::send(client, asset, checksum, || client.post(Upload::URL))
+    }
 
-        let response = client
-            .post(Upload::URL)
-            .set("Content-Type", &content_type)
-            .set("x-immich-checksum", &asset.checksum())
-            .auth(client.auth())
-            .send_bytes(&data)?;
-        Ok(response)
+    /// Replaces the file of the existing asset `id` with the data of `asset`
+    pub fn put(
+        client: &Client,
+        asset: &Asset,
+        id: &AssetId,
+        checksum: &str,
+    ) -> ImmichResult<Response> {
+        let url = format!("{}/{id}/original", Upload::URL);
+        Upload::send(client, asset, checksum, || client.put(&url))
+    }
+
+    fn send<F: Fn() -> Request>(
+        client: &Client,
+        asset: &Asset,
+        checksum: &str,
+        request: F,
+    ) -> ImmichResult<Response> {
+        let mut attempt = 0;
+        loop {
+            // the body is streamed from the asset, so it is created again for every attempt
+            let (content_type, content_length, body) = Upload::format_data(asset)?;
+            let result = request()
+                .set("Content-Type", &content_type)
+                .set("Content-Length", &content_length.to_string())
+                .set("x-immich-checksum", checksum)
+                .auth(client.auth())
+                .send(body);
+            match result {
+                Err(ureq::Error::Status(429, response)) if attempt < Upload::MAX_RETRIES => {
+                    let delay = Upload::retry_delay(response.header("Retry-After"), attempt);
+                    warn!(
+                        "Server is rate limiting, retrying {} in {delay:?}",
+                        asset.device_asset_id()
+                    );
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return Client::read_response(result),
+            }
+        }
+    }
+
+    /// Returns how long to wait before retrying a rate limited upload
+    ///
+    /// Uses the `Retry-After` seconds of the server, or an exponential backoff otherwise.
+    /// Both are limited to [`Upload::MAX_BACKOFF`]. Up to 50% random jitter is added,
+    /// so that the parallel upload threads don't retry all at the same time.
+    fn retry_delay(retry_after: Option<&str>, attempt: u32) -> Duration {
+        let delay = retry_after
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or_else(|| Duration::from_secs(1).saturating_mul(2u32.saturating_pow(attempt)))
+            .min(Upload::MAX_BACKOFF);
+        let jitter = RandomState::new().build_hasher().finish() % 500;
+        delay + delay.mul_f64(jitter as f64 / 1000.0)
     }
 
-    fn format_data(asset: &Asset) -> ImmichResult<(String, Vec<u8>)> {
-        Ok(MultipartBuilder::new()
+    /// Creates the multipart form of the asset
+    ///
+    /// The media data is streamed from [`Asset::reader`] and never held in memory as a whole.
+    /// Returns the content type, the content length and the body.
+    fn format_data(asset: &Asset) -> ImmichResult<(String, u64, impl Read + '_)> {
+        let mut form = MultipartBuilder::new()
             .add_text("deviceAssetId", asset.device_asset_id())?
             .add_text("deviceId", asset.device_id())?
             .add_text("fileCreatedAt", &asset.created_at().to_string())?
-            .add_text("fileModifiedAt", &asset.modified_at().to_string())?
-            .add_bytes(
-                asset.asset_data(),
-                "assetData",
-                Some(asset.device_asset_id()),
-            )?
-            .finish()?)
+            .add_text("fileModifiedAt", &asset.modified_at().to_string())?;
+        // only send the optional metadata that is set
+        if let Some(favorite) = asset.favorite() {
+            form = form.add_text("isFavorite", &favorite.to_string())?;
+        }
+        if let Some(sidecar) = asset.xmp_sidecar() {
+            form = form.add_bytes(
+                sidecar.as_bytes(),
+                "sidecarData",
+                Some(&format!("{}.xmp", asset.filename())),
+            )?;
+        }
+        Ok(form.finish_with_reader(
+            asset.reader()?,
+            asset.data_len()?,
+            "assetData",
+            Some(asset.filename()),
+        )?)
     }
 }
 
@@ -124,10 +364,14 @@ impl ParallelUpload {
         Self { threads }
     }
 
+    /// Spawns the upload threads
+    ///
+    /// Every asset is tagged with its position in the input, so that the results
+    /// can be returned in the same order.
     fn upload(
         &self,
-        receiver: Receiver<Asset>,
-        sender: Sender<Uploaded>,
+        receiver: Receiver<(usize, Asset, Option<String>)>,
+        sender: Sender<(usize, Uploaded)>,
         client: &Client,
     ) -> Vec<JoinHandle<()>> {
         (0..self.threads)
@@ -137,10 +381,27 @@ impl ParallelUpload {
                 let client = client.clone();
 
                 thread::spawn(move || {
-                    while let Ok(mut asset) = rec.recv() {
-                        let _ = match asset.upload(&client) {
-                            Ok(response) => res.send(response),
-                            Err(_) => res.send(Uploaded::from_failure(asset.device_asset_id())),
+                    while let Ok((index, mut asset, checksum)) = rec.recv() {
+                        let valid = if client.validate_uploads() {
+                            asset.validate()
+                        } else {
+                            Ok(())
+                        };
+                        // the checksum is only known if the assets were deduplicated locally
+                        let _ = match valid
+                            .and_then(|_| checksum.map_or_else(|| asset.checksum(), Ok))
+                            .and_then(|checksum| {
+                                let response = asset.upload_with_checksum(&client, &checksum)?;
+                                Self::verify(&client, &asset, &checksum, response)
+                            }) {
+                            Ok(response) => res.send((index, response)),
+                            Err(err) => {
+                                warn!("Failed to upload {}: {err}", asset.device_asset_id());
+                                res.send((
+                                    index,
+                                    Uploaded::from_failure(asset.device_asset_id(), &err),
+                                ))
+                            }
                         };
                     }
                 })
@@ -154,16 +415,31 @@ impl ParallelUpload {
         assets: I,
         feedback: Option<Sender<Uploaded>>,
     ) -> ImmichResult<Vec<Uploaded>> {
-        let (asset_sender, asset_receiver) = bounded::<Asset>(self.threads * 2);
+        self.post_fallible(client, assets.map(Ok::<Asset, Infallible>), feedback)
+    }
+
+    /// Uploads all valid assets and reports the invalid ones as [`Status::Failure`]
+    ///
+    /// The `feedback` channel receives the results as soon as each upload finishes,
+    /// the returned results are in the same order as the input `assets`.
+    pub fn post_fallible<E: Display, I: Iterator<Item = Result<Asset, E>>>(
+        &self,
+        client: &Client,
+        assets: I,
+        feedback: Option<Sender<Uploaded>>,
+    ) -> ImmichResult<Vec<Uploaded>> {
+        let capacity = client.upload_buffer().unwrap_or(self.threads * 2);
+        let (asset_sender, asset_receiver) = bounded::<(usize, Asset, Option<String>)>(capacity);
 
-        let (result_sender, result_receiver) = unbounded::<Uploaded>();
+        let (result_sender, result_receiver) = unbounded::<(usize, Uploaded)>();
 
-        let threads = self.upload(asset_receiver, result_sender, client);
+        let threads = self.upload(asset_receiver, result_sender.clone(), client);
+        let skipped_feedback = feedback.clone();
 
         let results = thread::spawn(move || {
             let mut result = Vec::new();
-            while let Ok(response) = result_receiver.recv() {
-                result.push(response.clone());
+            while let Ok((index, response)) = result_receiver.recv() {
+                result.push((index, response.clone()));
                 if let Some(channel) = &feedback {
                     channel
                         .send(response)
@@ -173,15 +449,510 @@ impl ParallelUpload {
             result
         });
 
-        for asset in assets {
-            asset_sender.send(asset)?
+        // checksum -> position and device asset id of the first asset with this content
+        let mut checksums: HashMap<String, (usize, String)> = HashMap::new();
+        // position and device asset id of the skipped asset and
+        // position and device asset id of the first asset
+        let mut skipped: Vec<(usize, String, (usize, String))> = Vec::new();
+
+        for (index, asset) in assets.enumerate() {
+            match asset {
+                Ok(asset) => {
+                    let mut checksum = None;
+                    if client.local_dedup() {
+                        let hash = match asset.checksum() {
+                            Ok(hash) => hash,
+                            Err(err) => {
+                                warn!("Failed to upload {}: {err}", asset.device_asset_id());
+                                let failed = Uploaded::from_failure(asset.device_asset_id(), &err);
+                                result_sender.send((index, failed))?;
+                                continue;
+                            }
+                        };
+                        match checksums.entry(hash) {
+                            Entry::Occupied(first) => {
+                                info!(
+                                    "Skipping {}, it has the same content as {}",
+                                    asset.device_asset_id(),
+                                    first.get().1
+                                );
+                                skipped.push((
+                                    index,
+                                    asset.device_asset_id().to_string(),
+                                    first.get().clone(),
+                                ));
+                                continue;
+                            }
+                            Entry::Vacant(entry) => {
+                                checksum = Some(entry.key().clone());
+                                entry.insert((index, asset.device_asset_id().to_string()));
+                            }
+                        }
+                    }
+                    asset_sender.send((index, asset, checksum))?
+                }
+                Err(err) => {
+                    warn!("Skipping invalid asset: {err}");
+                    result_sender.send((index, Uploaded::from_failure("", &err)))?
+                }
+            }
         }
         drop(asset_sender);
+        drop(result_sender);
 
         for thread in threads {
             thread.join().map_err(|_| ImmichError::Multithread)?
         }
 
-        results.join().map_err(|_| ImmichError::Multithread)
+        let mut results = results.join().map_err(|_| ImmichError::Multithread)?;
+        let duplicates = ParallelUpload::resolve_skipped(&results, skipped);
+        for (index, duplicate) in duplicates {
+            if let Some(channel) = &skipped_feedback {
+                channel
+                    .send(duplicate.clone())
+                    .expect("The feedback channel must remain open throughout");
+            }
+            results.push((index, duplicate));
+        }
+        results.sort_unstable_by_key(|(index, _)| *index);
+        Ok(results.into_iter().map(|(_, uploaded)| uploaded).collect())
+    }
+
+    /// Verifies the checksum of newly created assets, if enabled
+    ///
+    /// Assets with a mismatching checksum are deleted from the server again,
+    /// so that they don't remain as orphans of a failed upload.
+    fn verify(
+        client: &Client,
+        asset: &Asset,
+        checksum: &str,
+        uploaded: Uploaded,
+    ) -> ImmichResult<Uploaded> {
+        if !client.verify_uploads() || uploaded.status() != &Status::Created {
+            return Ok(uploaded);
+        }
+        if asset.verify_checksum(client, checksum)? {
+            Ok(uploaded)
+        } else {
+            if let Err(err) = asset.delete_permanently(client) {
+                warn!(
+                    "Failed to delete {} with a mismatching checksum, its id is {}: {err}",
+                    asset.device_asset_id(),
+                    asset.id()
+                );
+            }
+            Err(ImmichError::InvalidAsset(
+                "The checksum on the server does not match".to_string(),
+            ))
+        }
+    }
+
+    /// Reports the locally skipped assets with the upload result of the first asset
+    fn resolve_skipped(
+        uploaded: &[(usize, Uploaded)],
+        skipped: Vec<(usize, String, (usize, String))>,
+    ) -> Vec<(usize, Uploaded)> {
+        let first_uploads: HashMap<usize, &Uploaded> = uploaded
+            .iter()
+            .map(|(index, upload)| (*index, upload))
+            .collect();
+        skipped
+            .into_iter()
+            .map(|(index, device_asset_id, (first_index, first))| {
+                let uploaded = match first_uploads
+                    .get(&first_index)
+                    .and_then(|upload| upload.id())
+                {
+                    Some(id) => Uploaded::from_duplicate(&device_asset_id, id.clone()),
+                    _ => Uploaded::from_failure(
+                        &device_asset_id,
+                        &format!("Same content as {first}, which failed to upload"),
+                    ),
+                };
+                (index, uploaded)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn report_invalid_assets() {
+        let client = Client::new(
+            "https://immich.example.com/api".try_into().unwrap(),
+            crate::auth::Authenticated::ApiKey("s3cr3t".to_string()),
+            Vec::new(),
+            ureq::agent(),
+        );
+        let assets = ["broken.jpg", "missing.jpg"]
+            .into_iter()
+            .map(|name| Err::<Asset, String>(format!("{name}: unreadable")));
+
+        let results = ParallelUpload::new(2)
+            .post_fallible(&client, assets, None)
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            assert_eq!(result.status(), &Status::Failure);
+            assert!(result.id().is_none());
+        }
+        assert_eq!(results[0].error(), Some("broken.jpg: unreadable"));
+    }
+
+    #[test]
+    fn upload_validation() {
+        let client = Client::mock(|_, _| {
+            (
+                201,
+                r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#
+                    .to_string(),
+            )
+        });
+        let assets = || {
+            [
+                Asset::from_bytes(b"\0\0\0\x18ftyphevc".to_vec(), "IMG_1.heic".to_string()),
+                Asset::from_bytes(b"not an image".to_vec(), "IMG_2.jpg".to_string()),
+            ]
+            .into_iter()
+        };
+
+        let results = ParallelUpload::new(2)
+            .post(&client, assets(), None)
+            .unwrap();
+        assert!(results
+            .iter()
+            .all(|result| result.status() == &Status::Created));
+
+        let client = client.with_upload_validation();
+        let results = ParallelUpload::new(2)
+            .post(&client, assets(), None)
+            .unwrap();
+        assert_eq!(results[0].status(), &Status::Created);
+        assert_eq!(results[1].status(), &Status::Failure);
+    }
+
+    #[test]
+    fn results_in_input_order() {
+        let client = Client::mock(|_, _| {
+            (
+                201,
+                r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#
+                    .to_string(),
+            )
+        });
+        let names: Vec<String> = (0..20).map(|i| format!("IMG_{i}.jpg")).collect();
+        let assets = names.iter().enumerate().map(|(i, name)| {
+            if i % 3 == 0 {
+                Err(format!("{name}: unreadable"))
+            } else {
+                Ok(Asset::from_bytes(b"\xFF\xD8\xFF".to_vec(), name.clone()))
+            }
+        });
+
+        let results = ParallelUpload::new(4)
+            .post_fallible(&client, assets, None)
+            .unwrap();
+
+        assert_eq!(results.len(), names.len());
+        for (i, (result, name)) in results.iter().zip(&names).enumerate() {
+            if i % 3 == 0 {
+                assert_eq!(result.error(), Some(format!("{name}: unreadable").as_str()));
+            } else {
+                assert_eq!(result.device_asset_id(), name);
+            }
+        }
+    }
+
+    #[test]
+    fn local_dedup() {
+        let posts = Arc::new(AtomicUsize::new(0));
+        let counter = posts.clone();
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("POST", "/assets") => {
+                counter.fetch_add(1, Ordering::SeqCst);
+                (
+                    201,
+                    r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#
+                        .to_string(),
+                )
+            }
+            _ => (404, String::new()),
+        })
+        .with_local_dedup();
+        let assets = [
+            ("IMG_1.jpg", b"\xFF\xD8\xFFimage"),
+            ("copy.jpg", b"\xFF\xD8\xFFimage"),
+            ("IMG_2.jpg", b"\xFF\xD8\xFFother"),
+        ]
+        .into_iter()
+        .map(|(name, data)| Asset::from_bytes(data.to_vec(), name.to_string()));
+
+        let results = ParallelUpload::new(2).post(&client, assets, None).unwrap();
+
+        assert_eq!(posts.load(Ordering::SeqCst), 2);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].device_asset_id(), "IMG_1.jpg");
+        assert_eq!(results[0].status(), &Status::Created);
+        assert_eq!(results[1].device_asset_id(), "copy.jpg");
+        assert_eq!(results[1].status(), &Status::Duplicate);
+        assert_eq!(results[1].id(), results[0].id());
+    }
+
+    #[test]
+    fn resolve_skipped_by_position() {
+        let id = AssetId::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap();
+        // two different assets with the same device asset id
+        let uploaded = [
+            (0, Uploaded::from_duplicate("IMG_1.jpg", id.clone())),
+            (1, Uploaded::from_failure("IMG_1.jpg", &"unreadable")),
+        ];
+        let skipped = vec![(2, "copy.jpg".to_string(), (0, "IMG_1.jpg".to_string()))];
+
+        let resolved = ParallelUpload::resolve_skipped(&uploaded, skipped);
+
+        assert_eq!(resolved.len(), 1);
+        assert_eq!(resolved[0].0, 2);
+        assert_eq!(resolved[0].1.status(), &Status::Duplicate);
+        assert_eq!(resolved[0].1.id(), Some(&id));
+    }
+
+    fn form_body(asset: &Asset) -> Vec<u8> {
+        let (_, length, mut reader) = Upload::format_data(asset).unwrap();
+        let mut body = Vec::new();
+        reader.read_to_end(&mut body).unwrap();
+        assert_eq!(body.len() as u64, length);
+        body
+    }
+
+    #[test]
+    fn optional_metadata() {
+        let asset = Asset::from_bytes(b"\xFF\xD8\xFFimage".to_vec(), "IMG_1.jpg".to_string());
+        let body = form_body(&asset);
+        let body = String::from_utf8_lossy(&body);
+        assert!(!body.contains("isFavorite"));
+        assert!(!body.contains("sidecarData"));
+
+        let asset = Asset::builder()
+            .device_asset_id("IMG_1.jpg")
+            .favorite(true)
+            .description("Fish & Chips")
+            .location(52.5186, -13.4081)
+            .data(b"\xFF\xD8\xFFimage".to_vec())
+            .build();
+        let body = form_body(&asset);
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("name=\"isFavorite\"\r\n\r\ntrue"));
+        assert!(body.contains("filename=\"IMG_1.jpg.xmp\""));
+        assert!(body.contains(">Fish &amp; Chips</rdf:li>"));
+        assert!(body.contains("<exif:GPSLatitude>52,31.116000N</exif:GPSLatitude>"));
+        assert!(body.contains("<exif:GPSLongitude>13,24.486000W</exif:GPSLongitude>"));
+    }
+
+    #[test]
+    fn upload_buffer() {
+        let produced = Arc::new(AtomicUsize::new(0));
+        let read_ahead = Arc::new(AtomicUsize::new(0));
+        let posts = AtomicUsize::new(0);
+        let (produced_in_handler, read_ahead_in_handler) = (produced.clone(), read_ahead.clone());
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("POST", "/assets") => {
+                let posts = posts.fetch_add(1, Ordering::SeqCst) + 1;
+                let ahead = produced_in_handler.load(Ordering::SeqCst) - posts;
+                read_ahead_in_handler.fetch_max(ahead, Ordering::SeqCst);
+                (
+                    201,
+                    r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#
+                        .to_string(),
+                )
+            }
+            _ => (404, String::new()),
+        })
+        .with_upload_buffer(0);
+        let assets = (0..5).map(|index| {
+            produced.fetch_add(1, Ordering::SeqCst);
+            Asset::from_bytes(b"\xFF\xD8\xFFimage".to_vec(), format!("IMG_{index}.jpg"))
+        });
+
+        let results = ParallelUpload::new(1).post(&client, assets, None).unwrap();
+
+        assert_eq!(results.len(), 5);
+        assert!(results
+            .iter()
+            .all(|result| result.status() == &Status::Created));
+        // only the asset that waits for the upload thread is read ahead
+        assert!(read_ahead.load(Ordering::SeqCst) <= 1);
+    }
+
+    #[test]
+    fn streamed_body() {
+        let data = std::fs::read("./utils/garden.jpg").unwrap();
+        let asset = Asset::from_path("./utils/garden.jpg".into()).unwrap();
+        let body = form_body(&asset);
+        let start = body
+            .windows(data.len())
+            .position(|window| window == data.as_slice())
+            .unwrap();
+        assert!(body[start + data.len()..].starts_with(b"\r\n--"));
+        assert!(body.ends_with(b"--\r\n"));
+    }
+
+    #[test]
+    fn multipart_filename() {
+        let asset = Asset::from_bytes(b"\xFF\xD8\xFFimage".to_vec(), "IMG_1.jpg".to_string());
+        let body = form_body(&asset);
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("name=\"assetData\"; filename=\"IMG_1.jpg\""));
+
+        let asset = Asset::builder()
+            .device_asset_id("backup-1234")
+            .filename("IMG_1.jpg")
+            .description("Garden")
+            .data(b"\xFF\xD8\xFFimage".to_vec())
+            .build();
+        let body = form_body(&asset);
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("name=\"deviceAssetId\"\r\n\r\nbackup-1234"));
+        assert!(body.contains("name=\"assetData\"; filename=\"IMG_1.jpg\""));
+        assert!(body.contains("filename=\"IMG_1.jpg.xmp\""));
+        assert!(!body.contains("filename=\"backup-1234"));
+    }
+
+    #[test]
+    fn verify_uploads() {
+        let deletes = Arc::new(AtomicUsize::new(0));
+        let counter = deletes.clone();
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("POST", "/assets") => (
+                201,
+                r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#
+                    .to_string(),
+            ),
+            ("GET", "/assets/f0edb589-1312-4161-b41e-0a18f127b3dd") => (
+                200,
+                r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "checksum": "Wb0ATKaFrLuBK0t/n0R6XvWRi7w="}"#
+                    .to_string(),
+            ),
+            ("DELETE", "/assets") => {
+                counter.fetch_add(1, Ordering::SeqCst);
+                (204, String::new())
+            }
+            _ => (404, String::new()),
+        })
+        .with_upload_verification();
+        let assets = [
+            ("IMG_1.jpg", b"\xFF\xD8\xFFimage"),
+            ("IMG_2.jpg", b"\xFF\xD8\xFFother"),
+        ]
+        .into_iter()
+        .map(|(name, data)| Asset::from_bytes(data.to_vec(), name.to_string()));
+
+        let results = ParallelUpload::new(2).post(&client, assets, None).unwrap();
+
+        assert_eq!(results[0].status(), &Status::Created);
+        assert_eq!(results[1].status(), &Status::Failure);
+        // the mismatching asset is removed from the server again
+        assert_eq!(deletes.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn replace_asset() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("PUT", "/assets/41a3a296-7e86-4eb4-8e44-aead03344fc9/original") => (
+                200,
+                r#"{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "status": "replaced"}"#
+                    .to_string(),
+            ),
+            _ => (404, String::new()),
+        });
+        let id = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+        let mut asset = Asset::from_bytes(b"\xFF\xD8\xFFimage".to_vec(), "IMG_1.jpg".to_string());
+
+        let replaced = asset.replace(&client, &id).unwrap();
+        assert_eq!(replaced.status(), &Status::Replaced);
+        assert_eq!(replaced.device_asset_id(), "IMG_1.jpg");
+        assert_eq!(asset.id(), &id);
+
+        assert!(matches!(
+            asset.replace(&client, &AssetId::nil()),
+            Err(ImmichError::InvalidId)
+        ));
+    }
+
+    #[test]
+    fn upload_summary() {
+        let created: Uploaded = serde_json::from_str(
+            r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#,
+        )
+        .unwrap();
+        let id = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+        let summary = UploadSummary::from(vec![
+            created.clone(),
+            Uploaded::from_duplicate("IMG_2.jpg", id.clone()),
+            Uploaded::from_failure("IMG_3.jpg", &"broken"),
+            created,
+        ]);
+
+        assert_eq!(summary.created(), 2);
+        assert_eq!(summary.duplicate(), 1);
+        assert_eq!(summary.failure(), 1);
+        assert_eq!(summary.total(), 4);
+        assert_eq!(summary.ids().len(), 3);
+        assert_eq!(summary.ids()[1], id);
+    }
+
+    #[test]
+    fn rate_limit_delay() {
+        for attempt in 0..5 {
+            let delay = Upload::retry_delay(Some("3"), attempt);
+            assert!(delay >= Duration::from_secs(3));
+            assert!(delay < Duration::from_millis(4500));
+        }
+
+        let first = Upload::retry_delay(None, 0);
+        assert!(first >= Duration::from_secs(1) && first < Duration::from_millis(1500));
+        let third = Upload::retry_delay(Some("Wed, 21 Oct 2015 07:28:00 GMT"), 2);
+        assert!(third >= Duration::from_secs(4) && third < Duration::from_secs(6));
+        let capped = Upload::retry_delay(None, 40);
+        assert!(capped >= Upload::MAX_BACKOFF && capped < Upload::MAX_BACKOFF * 2);
+        let capped = Upload::retry_delay(Some("86400"), 0);
+        assert!(capped >= Upload::MAX_BACKOFF && capped < Upload::MAX_BACKOFF * 2);
+    }
+
+    #[test]
+    fn deserialize_minimal_response() {
+        let uploaded: Uploaded = serde_json::from_str(
+            r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#,
+        )
+        .unwrap();
+        assert_eq!(uploaded.status(), &Status::Created);
+        assert!(uploaded.asset_type().is_none());
+        assert!(uploaded.file_created_at().is_none());
+    }
+
+    #[test]
+    fn details_from_uploaded_asset() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("POST", "/assets") => (
+                201,
+                r#"{"id": "f0edb589-1312-4161-b41e-0a18f127b3dd", "status": "created"}"#
+                    .to_string(),
+            ),
+            _ => (500, String::new()),
+        });
+        let mut asset = Asset::from_bytes(b"video".to_vec(), "VID_1.mp4".to_string());
+        *asset.asset_type_mut() = AssetType::Video;
+        let uploaded = asset.upload(&client).unwrap();
+        assert_eq!(uploaded.status(), &Status::Created);
+        assert_eq!(uploaded.device_asset_id(), "VID_1.mp4");
+        assert_eq!(uploaded.asset_type(), Some(&AssetType::Video));
+        assert_eq!(uploaded.original_file_name(), Some("VID_1.mp4"));
+        assert_eq!(uploaded.file_created_at(), Some(asset.created_at()));
+        assert_eq!(uploaded.file_modified_at(), Some(asset.modified_at()));
     }
 }
@@ -1,28 +1,35 @@
-use std::{fs::File, io::Read, path::PathBuf};
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+};
 
-use serde::Deserialize;
+use log::warn;
+use serde::{Deserialize, Serialize};
 use sha1_smol::Sha1;
+use time::UtcOffset;
 use ureq::Response;
 
+use crate::api::requests::{AssetIds, DeleteAssets, UpdateAsset};
 use crate::takeout::Record;
 use crate::upload::{Upload, Uploaded};
-use crate::utils::{DateTime, Id, User, CLIENT_NAME};
+use crate::utils::{base64_to_hex, DateTime, Id, User, CLIENT_NAME};
 use crate::{Client, ImmichError, ImmichResult};
 
 pub type AssetId = Id;
 
-#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
 /// Different types of [`Asset`]
 pub enum AssetType {
-    #[serde(rename(deserialize = "IMAGE"))]
+    #[serde(rename = "IMAGE")]
     Image,
-    #[serde(rename(deserialize = "VIDEO"))]
+    #[serde(rename = "VIDEO")]
     Video,
-    #[serde(rename(deserialize = "AUDIO"))]
+    #[serde(rename = "AUDIO")]
     Audio,
-    #[serde(rename(deserialize = "OTHER"))]
+    #[serde(rename = "OTHER")]
     Other,
-    #[serde(rename(deserialize = "UNKNOWN"))]
+    #[serde(rename = "UNKNOWN")]
     Unknown,
 }
 
@@ -32,6 +39,75 @@ impl Default for AssetType {
     }
 }
 
+impl AssetType {
+    const IMAGE_EXTENSIONS: [&'static str; 9] = [
+        "jpg", "jpeg", "png", "webp", "heic", "heif", "avif", "gif", "tiff",
+    ];
+    const VIDEO_EXTENSIONS: [&'static str; 6] = ["mp4", "m4v", "mov", "webm", "3gp", "avi"];
+    const AUDIO_EXTENSIONS: [&'static str; 5] = ["mp3", "m4a", "wav", "ogg", "flac"];
+
+    /// Derives the `AssetType` from a (case-insensitive) file extension
+    pub(crate) fn from_extension(ext: &str) -> Self {
+        let ext = ext.to_lowercase();
+        if Self::IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+            Self::Image
+        } else if Self::VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+            Self::Video
+        } else if Self::AUDIO_EXTENSIONS.contains(&ext.as_str()) {
+            Self::Audio
+        } else {
+            Self::Unknown
+        }
+    }
+
+    /// Derives the `AssetType` and the usual file extension from the first bytes
+    /// (magic bytes) of the media data
+    ///
+    /// Returns `None` if the data does not start with a known signature
+    fn from_signature(header: &[u8]) -> Option<(Self, &'static str)> {
+        let at =
+            |offset: usize, magic: &[u8]| header.get(offset..offset + magic.len()) == Some(magic);
+        if at(0, &[0xFF, 0xD8, 0xFF]) {
+            Some((Self::Image, "jpg"))
+        } else if at(0, b"\x89PNG") {
+            Some((Self::Image, "png"))
+        } else if at(0, b"GIF8") {
+            Some((Self::Image, "gif"))
+        } else if at(0, b"II*\0") || at(0, b"MM\0*") {
+            Some((Self::Image, "tiff"))
+        } else if at(0, b"RIFF") && at(8, b"WEBP") {
+            Some((Self::Image, "webp"))
+        } else if at(4, b"ftyp") {
+            // ISO base media files (HEIC, MP4, MOV, M4A, ...) are distinguished by their brand
+            match header.get(8..12) {
+                Some(
+                    b"heic" | b"heix" | b"heim" | b"heis" | b"hevc" | b"hevx" | b"mif1" | b"msf1",
+                ) => Some((Self::Image, "heic")),
+                Some(b"avif" | b"avis") => Some((Self::Image, "avif")),
+                Some(b"M4A ") => Some((Self::Audio, "m4a")),
+                Some(b"qt  ") => Some((Self::Video, "mov")),
+                _ => Some((Self::Video, "mp4")),
+            }
+        } else if at(0, &[0x1A, 0x45, 0xDF, 0xA3]) {
+            Some((Self::Video, "mkv"))
+        } else if at(0, b"RIFF") && at(8, b"AVI ") {
+            Some((Self::Video, "avi"))
+        } else if at(0, b"OggS") {
+            Some((Self::Audio, "ogg"))
+        } else if at(0, b"fLaC") {
+            Some((Self::Audio, "flac"))
+        } else if at(0, b"RIFF") && at(8, b"WAVE") {
+            Some((Self::Audio, "wav"))
+        } else if at(0, b"ID3")
+            || (header.len() > 1 && header[0] == 0xFF && header[1] & 0xE0 == 0xE0)
+        {
+            Some((Self::Audio, "mp3"))
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Deserialize)]
 /// The status of the [`Asset`] on the remote Immich server
 pub enum AssetRemoteStatus {
@@ -46,6 +122,39 @@ impl Default for AssetRemoteStatus {
     }
 }
 
+/// Size of the preview image that Immich generates for an [`Asset`], see [`Asset::thumbnail`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ThumbnailSize {
+    /// Small image for overviews, e.g. the timeline
+    #[default]
+    Thumbnail,
+    /// Large image for the detail view of the asset
+    Preview,
+}
+
+impl ThumbnailSize {
+    fn as_query(&self) -> &'static str {
+        match self {
+            Self::Thumbnail => "thumbnail",
+            Self::Preview => "preview",
+        }
+    }
+}
+
+/// The source of the media data of an [`Asset`]
+enum AssetData {
+    /// The data is kept in memory
+    Bytes(Vec<u8>),
+    /// The data is read from the file when needed
+    File(PathBuf),
+}
+
+impl Default for AssetData {
+    fn default() -> Self {
+        Self::Bytes(Vec::new())
+    }
+}
+
 #[allow(non_snake_case)]
 #[derive(Deserialize)]
 /// An `Asset` is an image, video, audio or other media item
@@ -63,7 +172,8 @@ pub struct Asset {
     id: AssetId,
     deviceAssetId: String,
     deviceId: String,
-    assetData: Vec<u8>,
+    #[serde(skip)]
+    assetData: AssetData,
     owner: Option<User>,
     #[serde(serialize_with = "serialize_timestamp")]
     fileCreatedAt: DateTime,
@@ -71,11 +181,169 @@ pub struct Asset {
     fileModifiedAt: DateTime,
     #[serde(rename = "type")]
     asset_type: AssetType,
+    #[serde(default)]
+    isExternal: bool,
+    #[serde(default)]
+    isFavorite: Option<bool>,
+    #[serde(skip)]
+    description: Option<String>,
+    #[serde(skip)]
+    location: Option<(f64, f64)>,
     #[serde(skip)]
     remote_status: AssetRemoteStatus,
+    #[serde(skip)]
+    filename: Option<String>,
 }
 
 impl Asset {
+    /// Returns an [`AssetBuilder`] to create a fully customized `Asset`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::{Asset, AssetType, DateTime};
+    ///
+    /// let asset = Asset::builder()
+    ///     .device_asset_id("garden.jpg")
+    ///     .device_id("my-script")
+    ///     .created_at(DateTime::new(2025, 1, 28, 5, 42, 36).unwrap())
+    ///     .modified_at(DateTime::new(2025, 1, 29, 8, 0, 0).unwrap())
+    ///     .asset_type(AssetType::Image)
+    ///     .data(std::fs::read("./utils/garden.jpg").unwrap())
+    ///     .build();
+    ///
+    /// assert_eq!(asset.device_asset_id(), "garden.jpg");
+    /// assert_eq!(asset.device_id(), "my-script");
+    /// assert_eq!(asset.modified_at().to_string(), "2025-01-29T08:00:00.000Z");
+    /// assert_eq!(asset.asset_type(), &AssetType::Image);
+    /// assert_eq!(asset.checksum().unwrap(), "4cb6bfc3d436c695b230d50cb5ab1d79eaf32f6e");
+    /// ```
+    pub fn builder() -> AssetBuilder {
+        AssetBuilder::default()
+    }
+
+    /// Creates an `Asset` from data that is already in memory
+    ///
+    /// Use this method if the media data does not come from a file, e.g. if it is generated
+    /// or loaded from a database. The timestamps default to `3. October 1990 19:00:00` and
+    /// can be changed with [`Asset::created_at_mut`] and [`Asset::modified_at_mut`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::{Asset, DateTime};
+    ///
+    /// let data = std::fs::read("./utils/garden.jpg").unwrap();
+    /// let mut asset = Asset::from_bytes(data, "garden.jpg".to_string());
+    /// *asset.created_at_mut() = DateTime::new(2025, 1, 28, 5, 42, 36).unwrap();
+    ///
+    /// assert_eq!(asset.device_asset_id(), "garden.jpg");
+    /// assert_eq!(asset.checksum().unwrap(), "4cb6bfc3d436c695b230d50cb5ab1d79eaf32f6e");
+    /// assert_eq!(asset.created_at().to_string(), "2025-01-28T05:42:36.000Z");
+    /// ```
+    pub fn from_bytes(data: Vec<u8>, device_asset_id: String) -> Self {
+        Self {
+            deviceAssetId: device_asset_id,
+            assetData: AssetData::Bytes(data),
+            ..Default::default()
+        }
+    }
+
+    /// Creates an `Asset` from a file on the local file system, without reading its data
+    ///
+    /// In contrast to [`Asset::try_from::<PathBuf>`], the file is only opened and read
+    /// when the data is needed, e.g. to calculate the [`Asset::checksum`] or during the upload.
+    /// This keeps the memory usage low if many assets are prepared at once.
+    ///
+    /// [`Asset::asset_data`] is always empty for such assets, use [`Asset::reader`] instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use std::path::PathBuf;
+    /// use immich::Asset;
+    ///
+    /// let asset = Asset::from_path(PathBuf::from("./utils/garden.jpg")).unwrap();
+    /// assert_eq!(asset.device_asset_id(), "garden.jpg");
+    /// assert!(asset.asset_data().is_empty());
+    /// assert_eq!(asset.checksum().unwrap(), "4cb6bfc3d436c695b230d50cb5ab1d79eaf32f6e");
+    ///
+    /// let mut data = Vec::new();
+    /// asset.reader().unwrap().read_to_end(&mut data).unwrap();
+    /// assert_eq!(data.len(), 165012);
+    /// ```
+    pub fn from_path(path: PathBuf) -> ImmichResult<Self> {
+        let file = File::open(&path)?;
+        let mut asset = Asset::from_file_metadata(&file);
+        asset.set_path(&path);
+        asset.assetData = AssetData::File(path);
+        Ok(asset)
+    }
+
+    /// Creates an `Asset` without data, using the timestamps of the file
+    fn from_file_metadata(file: &File) -> Self {
+        let mut asset = Asset::default();
+
+        match file.metadata() {
+            Ok(meta) => {
+                match meta.created() {
+                    Ok(time) => asset.fileCreatedAt = time.into(),
+                    Err(err) => warn!("Cannot get creation timestamp from file: {err}"),
+                }
+                match meta.modified() {
+                    Ok(time) => asset.fileModifiedAt = time.into(),
+                    Err(err) => warn!("Cannot get modification timestamp from file: {err}"),
+                }
+            }
+            Err(err) => warn!("Cannot read file metadata: {err}"),
+        }
+        asset.deviceAssetId = format!("{CLIENT_NAME} - {}", asset.fileCreatedAt.filename());
+        asset
+    }
+
+    /// Reads the whole file into the asset and detects its type from the file signature
+    ///
+    /// Returns the file extension of the detected type, if any.
+    fn read_file(mut file: File) -> ImmichResult<(Self, Option<&'static str>)> {
+        let mut asset = Asset::from_file_metadata(&file);
+        let mut data = Vec::new();
+        let _ = file.read_to_end(&mut data)?;
+        let extension = AssetType::from_signature(&data).map(|(asset_type, extension)| {
+            asset.asset_type = asset_type;
+            extension
+        });
+        asset.assetData = AssetData::Bytes(data);
+        Ok((asset, extension))
+    }
+
+    /// Appends the start of the checksum to the device asset id
+    ///
+    /// Assets without a filename get a device asset id from their creation timestamp,
+    /// which is not unique for files that were created in the same second.
+    fn add_checksum_suffix(&mut self) -> ImmichResult<()> {
+        let checksum = self.checksum()?;
+        self.deviceAssetId.push_str(" - ");
+        self.deviceAssetId.push_str(&checksum[..8]);
+        Ok(())
+    }
+
+    /// Sets the device asset id, the filename and the asset type from the file path
+    fn set_path(&mut self, path: &Path) {
+        if let Some(name) = path.file_name() {
+            self.deviceAssetId.clear();
+            self.deviceAssetId.push_str(&name.to_string_lossy());
+            self.filename = Some(name.to_string_lossy().to_string());
+        }
+        if let Some(ext) = path.extension() {
+            self.asset_type = AssetType::from_extension(&ext.to_string_lossy());
+        }
+    }
+
     /// The Immich id of the asset
     ///
     /// This value will only useful after uploading the asset to the Immich server
@@ -102,6 +370,10 @@ impl Asset {
         &self.id
     }
 
+    pub(crate) fn id_mut(&mut self) -> &mut AssetId {
+        &mut self.id
+    }
+
     /// The client-id of the asset, usually the filename
     ///
     /// # Examples
@@ -128,10 +400,37 @@ impl Asset {
     /// let mut asset: Asset = Asset::try_from(PathBuf::from("./utils/garden.jpg")).unwrap();
     /// *asset.device_asset_id_mut() = "some_other_name.jpg".to_string();
     /// ```
+    ///
+    /// # Note
+    ///
+    /// Immich does not allow to change the device asset id after the upload,
+    /// so set it before uploading the asset.
     pub fn device_asset_id_mut(&mut self) -> &mut String {
         &mut self.deviceAssetId
     }
 
+    /// The filename that is sent to the Immich server during the upload
+    ///
+    /// Immich stores it as the original filename of the asset. It is the name of the
+    /// original file if known and falls back to [`Asset::device_asset_id`] otherwise.
+    /// In contrast to the device asset id, it is not changed by [`Asset::device_asset_id_mut`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use immich::Asset;
+    ///
+    /// let mut asset: Asset = Asset::try_from(PathBuf::from("./utils/garden.jpg")).unwrap();
+    /// *asset.device_asset_id_mut() = "holiday-2025-garden".to_string();
+    ///
+    /// assert_eq!(asset.device_asset_id(), "holiday-2025-garden");
+    /// assert_eq!(asset.filename(), "garden.jpg");
+    /// ```
+    pub fn filename(&self) -> &str {
+        self.filename.as_deref().unwrap_or(&self.deviceAssetId)
+    }
+
     /// The id of this client
     ///
     /// Defaults to `"Immich-<VERSION> (Rust Client)"`
@@ -167,6 +466,22 @@ impl Asset {
         &self.fileCreatedAt
     }
 
+    /// A mutable reference to the creation timestamp of the asset
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use immich::{Asset, DateTime};
+    ///
+    /// let mut asset: Asset = Asset::try_from(PathBuf::from("./utils/garden.jpg")).unwrap();
+    /// *asset.created_at_mut() = DateTime::new(2020, 5, 17, 14, 30, 0).unwrap();
+    /// assert_eq!(asset.created_at().to_string(), "2020-05-17T14:30:00.000Z");
+    /// ```
+    pub fn created_at_mut(&mut self) -> &mut DateTime {
+        &mut self.fileCreatedAt
+    }
+
     /// Timestamp of the last modification time of the asset
     ///
     /// If the asset is derived from a file, the `mtime` attribute is used. If `mtime` cannot
@@ -185,8 +500,111 @@ impl Asset {
         &self.fileModifiedAt
     }
 
+    /// A mutable reference to the last modification timestamp of the asset
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use immich::{Asset, DateTime};
+    ///
+    /// let mut asset: Asset = Asset::try_from(PathBuf::from("./utils/garden.jpg")).unwrap();
+    /// *asset.modified_at_mut() = DateTime::new(2020, 5, 17, 14, 30, 0).unwrap();
+    /// assert_eq!(asset.modified_at().to_string(), "2020-05-17T14:30:00.000Z");
+    /// ```
+    pub fn modified_at_mut(&mut self) -> &mut DateTime {
+        &mut self.fileModifiedAt
+    }
+
+    /// Whether the asset is marked as favorite during the upload
+    ///
+    /// `None` leaves the decision to the server, which does not mark new assets as favorite.
+    pub fn favorite(&self) -> Option<bool> {
+        self.isFavorite
+    }
+
+    /// A mutable reference to the favorite flag of the asset, see [`Asset::favorite`]
+    pub fn favorite_mut(&mut self) -> &mut Option<bool> {
+        &mut self.isFavorite
+    }
+
+    /// The description that is sent along with the upload
+    ///
+    /// The description is sent in an XMP sidecar, so it takes precedence over the
+    /// description in the EXIF data of the file.
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// A mutable reference to the description of the asset, see [`Asset::description`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::Asset;
+    ///
+    /// let mut asset = Asset::from_bytes(b"data".to_vec(), "IMG_1.jpg".to_string());
+    /// *asset.description_mut() = Some("Sunset at the beach".to_string());
+    /// *asset.location_mut() = Some((52.5186, 13.4081));
+    /// *asset.favorite_mut() = Some(true);
+    /// assert_eq!(asset.description(), Some("Sunset at the beach"));
+    /// ```
+    pub fn description_mut(&mut self) -> &mut Option<String> {
+        &mut self.description
+    }
+
+    /// Latitude and longitude that are sent along with the upload
+    ///
+    /// Like the [`Asset::description`], the location is sent in an XMP sidecar.
+    pub fn location(&self) -> Option<(f64, f64)> {
+        self.location
+    }
+
+    /// A mutable reference to the location of the asset, see [`Asset::location`]
+    pub fn location_mut(&mut self) -> &mut Option<(f64, f64)> {
+        &mut self.location
+    }
+
+    /// An XMP sidecar with the description and location, if any of them is set
+    pub(crate) fn xmp_sidecar(&self) -> Option<String> {
+        if self.description.is_none() && self.location.is_none() {
+            return None;
+        }
+        let mut properties = String::new();
+        if let Some(description) = &self.description {
+            properties.push_str(&format!(
+                "<dc:description><rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt></dc:description>",
+                xml_escape(description)
+            ));
+        }
+        if let Some((latitude, longitude)) = self.location {
+            properties.push_str(&format!(
+                "<exif:GPSLatitude>{}</exif:GPSLatitude><exif:GPSLongitude>{}</exif:GPSLongitude>",
+                xmp_coordinate(latitude, 'N', 'S'),
+                xmp_coordinate(longitude, 'E', 'W')
+            ));
+        }
+        Some(format!(
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\
+             <rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\
+             <rdf:Description rdf:about=\"\" \
+             xmlns:dc=\"http://purl.org/dc/elements/1.1/\" \
+             xmlns:exif=\"http://ns.adobe.com/exif/1.0/\">\
+             {properties}</rdf:Description></rdf:RDF></x:xmpmeta>"
+        ))
+    }
+
+    /// Converts the creation and modification timestamps to the local time of a UTC offset
+    pub(crate) fn apply_offset(&mut self, offset: UtcOffset) {
+        self.fileCreatedAt = self.fileCreatedAt.to_offset(offset);
+        self.fileModifiedAt = self.fileModifiedAt.to_offset(offset);
+    }
+
     /// The actual media asset's data
     ///
+    /// This is empty for assets that read their data lazily (see [`Asset::from_path`]).
+    /// Use [`Asset::reader`] to read the data of any asset.
+    ///
     /// # Examples
     ///
     /// ```
@@ -197,7 +615,46 @@ impl Asset {
     /// assert_eq!(asset.asset_data().len(), 165012);
     /// ```
     pub fn asset_data(&self) -> &[u8] {
-        &self.assetData
+        match &self.assetData {
+            AssetData::Bytes(data) => data,
+            AssetData::File(_) => &[],
+        }
+    }
+
+    /// Returns a reader over the actual media asset's data
+    ///
+    /// The data is read from memory or, for assets created with [`Asset::from_path`],
+    /// from the file on the local file system.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file of the asset cannot be opened
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Read;
+    /// use immich::Asset;
+    ///
+    /// let asset = Asset::from_bytes(b"image data".to_vec(), "image.jpg".to_string());
+    ///
+    /// let mut data = String::new();
+    /// asset.reader().unwrap().read_to_string(&mut data).unwrap();
+    /// assert_eq!(data, "image data");
+    /// ```
+    pub fn reader(&self) -> ImmichResult<Box<dyn Read + '_>> {
+        match &self.assetData {
+            AssetData::Bytes(data) => Ok(Box::new(data.as_slice())),
+            AssetData::File(path) => Ok(Box::new(File::open(path)?)),
+        }
+    }
+
+    /// The number of bytes of the media data
+    pub(crate) fn data_len(&self) -> ImmichResult<u64> {
+        match &self.assetData {
+            AssetData::Bytes(data) => Ok(data.len() as u64),
+            AssetData::File(path) => Ok(std::fs::metadata(path)?.len()),
+        }
     }
 
     /// The owner of the asset on the Immich server
@@ -227,18 +684,49 @@ impl Asset {
 
     /// The [`AssetType`] of the asset
     ///
-    /// This can be `Image`, `Video`, `Other`
+    /// This can be `Image`, `Video`, `Audio`, `Other`
+    ///
+    /// Assets created from local files derive the type from the file extension.
     ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use immich::{Asset, AssetType};
+    ///
+    /// let asset: Asset = Asset::try_from(PathBuf::from("./utils/garden.jpg")).unwrap();
+    /// assert_eq!(asset.asset_type(), &AssetType::Image);
+    /// ```
     pub fn asset_type(&self) -> &AssetType {
         &self.asset_type
     }
 
     /// Mutable refernce to the [`AssetType`] of the asset
     ///
-    /// This can be `Image`, `Video`, `Other`
+    /// This can be `Image`, `Video`, `Audio`, `Other`
     ///
-    pub fn asset_type_mut(&mut self) -> &AssetType {
-        &self.asset_type
+    /// Use it to correct the type before the upload, if it was not inferred correctly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::{Asset, AssetType};
+    ///
+    /// let mut asset = Asset::from_bytes(b"data".to_vec(), "recording".to_string());
+    /// *asset.asset_type_mut() = AssetType::Video;
+    /// assert_eq!(asset.asset_type(), &AssetType::Video);
+    /// ```
+    pub fn asset_type_mut(&mut self) -> &mut AssetType {
+        &mut self.asset_type
+    }
+
+    /// Returns `true` if the asset belongs to an external library on the Immich server
+    ///
+    /// Assets of external libraries are read-only, they can't be modified or deleted.
+    /// This value is only set for assets that were fetched from the server, e.g. with
+    /// [`Client::trashed_assets`].
+    pub fn is_external(&self) -> bool {
+        self.isExternal
     }
 
     /// The status of the asset on the remote Immich server
@@ -256,6 +744,10 @@ impl Asset {
 
     /// The SHA1 checksum of the asset
     ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::IoAt`] if the file of the asset can't be read completely
+    ///
     /// # Examples
     ///
     /// ```
@@ -263,14 +755,100 @@ impl Asset {
     /// use immich::Asset;
     ///
     /// let mut asset: Asset = Asset::try_from(PathBuf::from("./utils/garden.jpg")).unwrap();
-    /// assert_eq!(asset.checksum(), "4cb6bfc3d436c695b230d50cb5ab1d79eaf32f6e");
+    /// assert_eq!(asset.checksum().unwrap(), "4cb6bfc3d436c695b230d50cb5ab1d79eaf32f6e");
     /// ```
-    pub fn checksum(&self) -> String {
-        Sha1::from(&self.assetData).hexdigest()
+    pub fn checksum(&self) -> ImmichResult<String> {
+        match &self.assetData {
+            AssetData::Bytes(data) => Ok(Sha1::from(data).hexdigest()),
+            AssetData::File(path) => {
+                let io_error = |source| ImmichError::IoAt {
+                    path: path.clone(),
+                    source,
+                };
+                let mut file = File::open(path).map_err(io_error)?;
+                let mut hasher = Sha1::new();
+                let mut buffer = [0; 8192];
+                loop {
+                    match file.read(&mut buffer) {
+                        Ok(0) => break,
+                        Ok(n) => hasher.update(&buffer[..n]),
+                        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => {}
+                        Err(err) => return Err(io_error(err)),
+                    }
+                }
+                Ok(hasher.hexdigest())
+            }
+        }
+    }
+
+    /// Checks that the asset contains valid media data
+    ///
+    /// The asset is rejected if its data is empty or if the first bytes of the data do not
+    /// match its [`AssetType`]. The type is derived from the file extension if it is unknown.
+    /// Images must start with a known image signature. Video and audio containers vary a lot,
+    /// so they are only rejected if they clearly contain another type of media.
+    ///
+    /// Parallel uploads, e.g. [`Client::upload`], validate every asset if the client is
+    /// created with [`Client::with_upload_validation`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidAsset`] with a description of the problem
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use immich::Asset;
+    ///
+    /// let asset = Asset::from_path(PathBuf::from("./utils/garden.jpg")).unwrap();
+    /// assert!(asset.validate().is_ok());
+    ///
+    /// let empty = Asset::from_bytes(Vec::new(), "empty.jpg".to_string());
+    /// assert!(empty.validate().is_err());
+    ///
+    /// let text = Asset::from_bytes(b"not an image".to_vec(), "text.jpg".to_string());
+    /// assert!(text.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> ImmichResult<()> {
+        let mut header = Vec::with_capacity(16);
+        let _ = self.reader()?.take(16).read_to_end(&mut header)?;
+        if header.is_empty() {
+            return Err(ImmichError::InvalidAsset(format!(
+                "{} is empty",
+                self.deviceAssetId
+            )));
+        }
+
+        let expected = match self.asset_type {
+            AssetType::Unknown => Path::new(&self.deviceAssetId)
+                .extension()
+                .map(|ext| AssetType::from_extension(&ext.to_string_lossy()))
+                .unwrap_or_default(),
+            ref asset_type => asset_type.clone(),
+        };
+        let detected = AssetType::from_signature(&header).map(|(detected, _)| detected);
+
+        let valid = match (&expected, &detected) {
+            (AssetType::Image, detected) => detected == &Some(AssetType::Image),
+            (AssetType::Video | AssetType::Audio, Some(detected)) => detected == &expected,
+            _ => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(ImmichError::InvalidAsset(format!(
+                "{} does not contain {:?} data",
+                self.deviceAssetId, expected
+            )))
+        }
     }
 
     /// Uploads the asset to the Immich remote server
     ///
+    /// If the server is rate limiting (`429 Too Many Requests`), the upload is paused
+    /// according to the `Retry-After` header and retried a few times.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -291,25 +869,495 @@ impl Asset {
     ///     "{}: {} [Remote ID: {}]",
     ///     upload_status.device_asset_id(),
     ///     upload_status.status(),
-    ///     upload_status.id()
+    ///     upload_status.id().map(ToString::to_string).unwrap_or_default()
     /// );
     /// ```
     pub fn upload(&mut self, client: &Client) -> ImmichResult<Uploaded> {
-        let resp = Upload::post(client, self)?;
-        match resp.status() {
-            201 | 200 => self.parse_upload(resp),
-            other => Err(ImmichError::Status(other, resp.into_string()?)),
+        let checksum = self.checksum()?;
+        self.upload_with_checksum(client, &checksum)
+    }
+
+    /// Uploads the asset like [`Asset::upload`], with the already known [`Asset::checksum`]
+    pub(crate) fn upload_with_checksum(
+        &mut self,
+        client: &Client,
+        checksum: &str,
+    ) -> ImmichResult<Uploaded> {
+        let resp = Upload::post(client, self, checksum)?;
+        self.parse_upload(resp)
+    }
+
+    /// Replaces the file of an existing asset on the Immich server with this asset's data
+    ///
+    /// Use this method to upgrade an asset, e.g. with a higher quality version of the image,
+    /// without creating a duplicate. The asset keeps its albums and other metadata on the
+    /// server. Afterwards, [`Asset::id`] refers to the replaced asset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidId`] if `id` is not a valid id and
+    /// [`ImmichError::Status`] if the server rejects the new file.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, AssetId, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let id = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+    /// let mut asset: Asset = std::path::PathBuf::from("/path/to/original").try_into().unwrap();
+    ///
+    /// let replaced = asset.replace(&client, &id).unwrap();
+    /// println!("{id}: {}", replaced.status());
+    /// ```
+    pub fn replace(&mut self, client: &Client, id: &AssetId) -> ImmichResult<Uploaded> {
+        if !id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        let resp = Upload::put(client, self, id, &self.checksum()?)?;
+        let uploaded = self.parse_upload(resp)?;
+        self.id = id.clone();
+        Ok(uploaded)
+    }
+
+    /// Sets the capture date of the asset on the Immich server to [`Asset::created_at`]
+    ///
+    /// Use this method if the server could not read the capture date from the media file
+    /// itself, e.g. because of missing EXIF data. For assets from a Google Takeout archive,
+    /// [`Asset::created_at`] is the date the photo or video was taken.
+    ///
+    /// Nothing happens if the asset was never uploaded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidId`] if the asset has an invalid id and
+    /// [`ImmichError::ExternalAsset`] if the asset belongs to an external library.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let mut asset: Asset = std::path::PathBuf::from("/path/to/image").try_into().unwrap();
+    /// asset.upload(&client).unwrap();
+    /// asset.sync_timestamps(&client).unwrap();
+    /// ```
+    pub fn sync_timestamps(&self, client: &Client) -> ImmichResult<()> {
+        if self.id == AssetId::default() {
+            return Ok(());
+        }
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        self.check_writable()?;
+        Client::read_empty(
+            client
+                .put(&format!("/assets/{}", self.id))
+                .send_json(UpdateAsset::date_time_original(&self.fileCreatedAt)),
+        )
+    }
+
+    /// Checks that the Immich server stored exactly the data of this asset
+    ///
+    /// Compares the checksum of the uploaded asset on the server with [`Asset::checksum`].
+    /// The asset must be uploaded first, e.g. with [`Asset::upload`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidId`] if the asset was never uploaded and
+    /// [`ImmichError::InvalidResponse`] if the server does not report a checksum.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let mut asset: Asset = std::path::PathBuf::from("/path/to/image").try_into().unwrap();
+    /// asset.upload(&client).unwrap();
+    /// assert!(asset.verify(&client).unwrap());
+    /// ```
+    pub fn verify(&self, client: &Client) -> ImmichResult<bool> {
+        self.verify_checksum(client, &self.checksum()?)
+    }
+
+    /// Verifies the asset like [`Asset::verify`], with the already known [`Asset::checksum`]
+    pub(crate) fn verify_checksum(&self, client: &Client, checksum: &str) -> ImmichResult<bool> {
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        let remote = client.asset(&self.id)?;
+        let remote_checksum = remote.checksum().ok_or(ImmichError::InvalidResponse)?;
+        Ok(remote_checksum == checksum)
+    }
+
+    /// Downloads the preview image that the Immich server generated for the asset
+    ///
+    /// Returns the encoded image data, usually a JPEG or WebP image. This is a lot smaller
+    /// than the original image or video, e.g. to show a review of the imported assets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::NotFound`] if the asset does not exist on the server or
+    /// if no preview was generated yet.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client, ThumbnailSize};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let mut asset: Asset = std::path::PathBuf::from("/path/to/image").try_into().unwrap();
+    /// asset.upload(&client).unwrap();
+    ///
+    /// let preview = asset.thumbnail(&client, ThumbnailSize::Preview).unwrap();
+    /// std::fs::write("/path/to/preview", preview).unwrap();
+    /// ```
+    pub fn thumbnail(&self, client: &Client, size: ThumbnailSize) -> ImmichResult<Vec<u8>> {
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        let response = Client::read_response(
+            client
+                .get(&format!("/assets/{}/thumbnail", self.id))
+                .query("size", size.as_query())
+                .call(),
+        )?;
+        let mut data = Vec::new();
+        response.into_reader().read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Moves the asset to the trash on the Immich server
+    ///
+    /// Trashed assets can be restored with [`Asset::restore`] until the trash is emptied.
+    /// Assets of external libraries can't be trashed, see [`Asset::is_external`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let mut asset: Asset = std::path::PathBuf::from("/path/to/image").try_into().unwrap();
+    /// asset.upload(&client).unwrap();
+    ///
+    /// asset.trash(&client).unwrap();
+    /// asset.restore(&client).unwrap();
+    /// ```
+    pub fn trash(&self, client: &Client) -> ImmichResult<()> {
+        self.delete(client, false)
+    }
+
+    /// Permanently deletes the asset from the Immich server
+    ///
+    /// The asset is not moved to the trash and cannot be restored.
+    /// Assets of external libraries can't be deleted, see [`Asset::is_external`].
+    pub fn delete_permanently(&self, client: &Client) -> ImmichResult<()> {
+        self.delete(client, true)
+    }
+
+    /// Restores the asset from the trash on the Immich server
+    pub fn restore(&self, client: &Client) -> ImmichResult<()> {
+        let payload: AssetIds = std::iter::once(self.id.clone()).into();
+        Client::read_empty(client.post("/trash/restore/assets").send_json(payload))
+    }
+
+    fn delete(&self, client: &Client, force: bool) -> ImmichResult<()> {
+        self.check_writable()?;
+        let payload = DeleteAssets::new(std::iter::once(self.id.clone()), force);
+        Client::read_empty(client.delete("/assets").send_json(payload))
+    }
+
+    /// Fails for assets of external libraries, which are read-only
+    fn check_writable(&self) -> ImmichResult<()> {
+        if self.is_external() {
+            Err(ImmichError::ExternalAsset(self.id.to_string()))
+        } else {
+            Ok(())
         }
     }
 
     fn parse_upload(&mut self, response: Response) -> ImmichResult<Uploaded> {
-        let mut response: Uploaded = response.into_json()?;
+        let response: Uploaded = response.into_json()?;
         self.remote_status = AssetRemoteStatus::Present;
-        self.id = response.id().clone();
-        response
-            .device_asset_id_mut()
-            .push_str(self.device_asset_id());
-        Ok(response)
+        if let Some(id) = response.id() {
+            self.id = id.clone();
+        }
+        Ok(response.with_asset(self))
+    }
+}
+
+/// Escapes the special characters of XML text
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Formats decimal degrees as XMP GPS coordinate, e.g. `52,31.116000N`
+fn xmp_coordinate(value: f64, positive: char, negative: char) -> String {
+    let direction = if value < 0.0 { negative } else { positive };
+    let value = value.abs();
+    let degrees = value.trunc();
+    format!(
+        "{},{:.6}{direction}",
+        degrees as u32,
+        (value - degrees) * 60.0
+    )
+}
+
+/// Builder to create an [`Asset`] with custom metadata, see [`Asset::builder`]
+///
+/// All values that are not set keep the defaults of [`Asset::from_bytes`].
+#[derive(Default)]
+pub struct AssetBuilder {
+    asset: Asset,
+}
+
+impl AssetBuilder {
+    /// Sets the client-side id of the asset, usually the filename
+    pub fn device_asset_id(mut self, device_asset_id: &str) -> Self {
+        self.asset.deviceAssetId = device_asset_id.to_string();
+        self
+    }
+
+    /// Sets the filename that is sent to the Immich server, see [`Asset::filename`]
+    pub fn filename(mut self, filename: &str) -> Self {
+        self.asset.filename = Some(filename.to_string());
+        self
+    }
+
+    /// Sets the id of the device that uploads the asset
+    pub fn device_id(mut self, device_id: &str) -> Self {
+        self.asset.deviceId = device_id.to_string();
+        self
+    }
+
+    /// Sets the creation timestamp
+    pub fn created_at(mut self, created_at: DateTime) -> Self {
+        self.asset.fileCreatedAt = created_at;
+        self
+    }
+
+    /// Sets the modification timestamp
+    pub fn modified_at(mut self, modified_at: DateTime) -> Self {
+        self.asset.fileModifiedAt = modified_at;
+        self
+    }
+
+    /// Sets the [`AssetType`]
+    pub fn asset_type(mut self, asset_type: AssetType) -> Self {
+        self.asset.asset_type = asset_type;
+        self
+    }
+
+    /// Marks the asset as favorite, or explicitly not
+    pub fn favorite(mut self, favorite: bool) -> Self {
+        self.asset.isFavorite = Some(favorite);
+        self
+    }
+
+    /// Sets the description
+    pub fn description(mut self, description: &str) -> Self {
+        self.asset.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the location in decimal degrees
+    pub fn location(mut self, latitude: f64, longitude: f64) -> Self {
+        self.asset.location = Some((latitude, longitude));
+        self
+    }
+
+    /// Sets the actual media data
+    pub fn data(mut self, data: Vec<u8>) -> Self {
+        self.asset.assetData = AssetData::Bytes(data);
+        self
+    }
+
+    /// Creates the [`Asset`]
+    pub fn build(self) -> Asset {
+        self.asset
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(Clone, Deserialize)]
+/// An image, video or other media item as reported by the Immich server
+///
+/// In contrast to [`Asset`], a `RemoteAsset` does not contain any media data. It is returned
+/// when reading assets from the server, e.g. with [`Client::asset`] or
+/// [`Album::assets`](crate::Album::assets).
+///
+/// # Examples
+///
+/// ```no_run
+/// use immich::{AssetId, Client};
+///
+/// let client = Client::with_email(
+///     "https://immich-web-url/api",
+///     "email@somewhere",
+///     "s3cr3tpassword"
+/// ).unwrap();
+///
+/// let id = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+/// let asset = client.asset(&id).unwrap();
+/// println!("{} was created at {}", asset.original_file_name(), asset.created_at());
+/// ```
+pub struct RemoteAsset {
+    id: AssetId,
+    #[serde(default)]
+    deviceAssetId: String,
+    #[serde(default)]
+    deviceId: String,
+    #[serde(default)]
+    ownerId: Id,
+    #[serde(default)]
+    owner: Option<User>,
+    #[serde(default, rename = "type")]
+    asset_type: AssetType,
+    #[serde(default)]
+    originalFileName: String,
+    #[serde(default)]
+    fileCreatedAt: DateTime,
+    #[serde(default)]
+    fileModifiedAt: DateTime,
+    #[serde(default)]
+    updatedAt: Option<DateTime>,
+    #[serde(default)]
+    isFavorite: bool,
+    #[serde(default)]
+    isArchived: bool,
+    #[serde(default)]
+    isTrashed: bool,
+    #[serde(default)]
+    isOffline: bool,
+    #[serde(default)]
+    isExternal: bool,
+    #[serde(default)]
+    libraryId: Option<Id>,
+    #[serde(default)]
+    checksum: String,
+}
+
+impl RemoteAsset {
+    /// The Immich id of the asset
+    pub fn id(&self) -> &AssetId {
+        &self.id
+    }
+
+    /// The client-id of the asset, usually the filename
+    pub fn device_asset_id(&self) -> &str {
+        &self.deviceAssetId
+    }
+
+    /// The id of the device that uploaded the asset
+    pub fn device_id(&self) -> &str {
+        &self.deviceId
+    }
+
+    /// The id of the user who owns the asset
+    pub fn owner_id(&self) -> &Id {
+        &self.ownerId
+    }
+
+    /// The user who owns the asset, if reported by the server
+    pub fn owner(&self) -> Option<&User> {
+        self.owner.as_ref()
+    }
+
+    /// The [`AssetType`] of the asset
+    pub fn asset_type(&self) -> &AssetType {
+        &self.asset_type
+    }
+
+    /// The original filename of the asset
+    pub fn original_file_name(&self) -> &str {
+        &self.originalFileName
+    }
+
+    /// The creation timestamp of the asset
+    pub fn created_at(&self) -> &DateTime {
+        &self.fileCreatedAt
+    }
+
+    /// The modification timestamp of the asset
+    pub fn modified_at(&self) -> &DateTime {
+        &self.fileModifiedAt
+    }
+
+    /// The timestamp of the last update of the asset on the server, if reported
+    pub fn updated_at(&self) -> Option<&DateTime> {
+        self.updatedAt.as_ref()
+    }
+
+    /// Returns `true` if the asset is marked as favorite
+    pub fn favorite(&self) -> bool {
+        self.isFavorite
+    }
+
+    /// Returns `true` if the asset is archived
+    pub fn archived(&self) -> bool {
+        self.isArchived
+    }
+
+    /// Returns `true` if the asset is in the trash
+    pub fn trashed(&self) -> bool {
+        self.isTrashed
+    }
+
+    /// Returns `true` if the file of an external library asset is missing on the server
+    pub fn offline(&self) -> bool {
+        self.isOffline
+    }
+
+    /// Returns `true` if the asset belongs to a read-only external library
+    pub fn is_external(&self) -> bool {
+        self.isExternal
+    }
+
+    /// The id of the external library of the asset
+    pub fn library_id(&self) -> Option<&Id> {
+        self.libraryId.as_ref()
+    }
+
+    /// The SHA1 checksum of the asset in hex format, like [`Asset::checksum`]
+    ///
+    /// Returns `None` if the server did not report a valid checksum
+    pub fn checksum(&self) -> Option<String> {
+        if self.checksum.is_empty() {
+            None
+        } else {
+            base64_to_hex(&self.checksum)
+        }
     }
 }
 
@@ -319,12 +1367,17 @@ impl Default for Asset {
             id: Id::default(),
             deviceAssetId: format!("{CLIENT_NAME}-empty"),
             deviceId: CLIENT_NAME.to_string(),
-            assetData: vec![],
+            assetData: AssetData::default(),
             owner: None,
             fileCreatedAt: DateTime::default(),
             fileModifiedAt: DateTime::default(),
             asset_type: AssetType::Unknown,
+            isExternal: false,
+            isFavorite: None,
+            description: None,
+            location: None,
             remote_status: AssetRemoteStatus::Unknown,
+            filename: None,
         }
     }
 }
@@ -349,15 +1402,38 @@ impl TryFrom<PathBuf> for Asset {
     /// assert!(asset.device_asset_id() == "garden.jpg");
     /// ```
     ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::IoAt`] with the path of the file if it can't be opened or read.
+    ///
+    /// ```
+    /// use std::io::ErrorKind;
+    /// use std::path::PathBuf;
+    /// use immich::{Asset, ImmichError};
+    ///
+    /// let path = PathBuf::from("./utils/missing.jpg");
+    /// match Asset::try_from(path.clone()) {
+    ///     Err(ImmichError::IoAt { path: failed, source }) => {
+    ///         assert_eq!(failed, path);
+    ///         assert_eq!(source.kind(), ErrorKind::NotFound);
+    ///     }
+    ///     _ => panic!("The file does not exist"),
+    /// }
+    /// ```
     fn try_from(path: PathBuf) -> Result<Self, Self::Error> {
-        let file = File::open(&path)?;
-
-        let mut asset = Asset::try_from(file)?;
+        let file = File::open(&path).map_err(|source| ImmichError::IoAt {
+            path: path.clone(),
+            source,
+        })?;
 
-        if let Some(name) = path.file_name() {
-            asset.deviceAssetId.clear();
-            asset.deviceAssetId.push_str(&name.to_string_lossy());
-        }
+        let (mut asset, _) = Asset::read_file(file).map_err(|err| match err {
+            ImmichError::Io { source } => ImmichError::IoAt {
+                path: path.clone(),
+                source,
+            },
+            err => err,
+        })?;
+        asset.set_path(&path);
         Ok(asset)
     }
 }
@@ -374,7 +1450,11 @@ impl TryFrom<File> for Asset {
     /// Use this method only, if you do not have access to the local file system.
     /// Consider using [`Asset::try_from::<PathBuf>`]. The Rust `File` object does
     /// not hold a reference to the filename so the library cannot set it properly.
-    /// It will build one using the creation timestamp instead
+    /// It will build one using the creation timestamp and the start of the
+    /// [`Asset::checksum`] instead, so that files created in the same second don't collide.
+    /// The [`Asset::filename`] and [`Asset::asset_type`] are derived from the content of the
+    /// file, e.g. `<device asset id>.jpg` for JPEG images, so that the server recognizes the
+    /// file type.
     ///
     /// # Examples
     ///
@@ -384,29 +1464,29 @@ impl TryFrom<File> for Asset {
     ///
     /// let file = File::open("./utils/garden.jpg").unwrap();
     /// let mut asset: Asset = Asset::try_from(file).unwrap();
-    /// assert_eq!(asset.device_asset_id(), "Immich-0.1 (Rust Client) - 20250128_054236");
+    /// assert_eq!(asset.device_asset_id(), "Immich-0.1 (Rust Client) - 20250128_054236 - 4cb6bfc3");
     /// ```
     ///
-    fn try_from(mut file: File) -> Result<Self, Self::Error> {
-        let mut asset = Asset::default();
-
-        if let Ok(meta) = file.metadata() {
-            if let Ok(time) = meta.created() {
-                asset.fileCreatedAt = time.into();
-            }
-            if let Ok(time) = meta.modified() {
-                asset.fileModifiedAt = time.into();
-            }
+    fn try_from(file: File) -> Result<Self, Self::Error> {
+        let (mut asset, extension) = Asset::read_file(file)?;
+        asset.add_checksum_suffix()?;
+        if let Some(extension) = extension {
+            asset.filename = Some(format!("{}.{extension}", asset.deviceAssetId));
         }
-        asset.deviceAssetId = format!("{CLIENT_NAME} - {}", asset.fileCreatedAt.filename());
-        let _ = file.read_to_end(&mut asset.assetData)?;
         Ok(asset)
     }
 }
 
-impl TryFrom<Record<'_>> for Asset {
+impl<R: Read> TryFrom<Record<'_, R>> for Asset {
     type Error = ImmichError;
-    fn try_from(mut record: Record) -> Result<Self, Self::Error> {
+    /// Create an [`Asset`] from a [`Record`] of a Google Takeout archive
+    ///
+    /// The creation timestamp is taken from [`Record::date_taken`] and the modification
+    /// timestamp from [`Record::date_modified`]. If the archive does not contain metadata
+    /// for the record, both fall back to the default timestamp.
+    ///
+    /// The favorite flag, description and location of the record are uploaded as well.
+    fn try_from(mut record: Record<'_, R>) -> Result<Self, Self::Error> {
         let mut asset = Asset::default();
 
         if let Some(date) = record.date_taken() {
@@ -419,10 +1499,92 @@ impl TryFrom<Record<'_>> for Asset {
 
         asset.deviceAssetId.clear();
         asset.deviceAssetId.push_str(record.name());
+        asset.filename = Some(record.name().to_string());
         asset.deviceId.push_str(" [Google Takeout Import]");
+        asset.asset_type = record.asset_type();
+        asset.isFavorite = record.favorite().then_some(true);
+        asset.description = record.description().map(str::to_string);
+        asset.location = record.location();
 
-        let _ = record.read_to_end(&mut asset.assetData)?;
+        let mut data = Vec::new();
+        let _ = record.read_to_end(&mut data)?;
+        asset.assetData = AssetData::Bytes(data);
 
         Ok(asset)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_device_asset_ids() {
+        let mut first = Asset::from_bytes(
+            b"\xFF\xD8\xFFimage".to_vec(),
+            format!("{CLIENT_NAME} - 20250128_054236"),
+        );
+        let mut second = Asset::from_bytes(
+            b"\xFF\xD8\xFFother".to_vec(),
+            format!("{CLIENT_NAME} - 20250128_054236"),
+        );
+        first.add_checksum_suffix().unwrap();
+        second.add_checksum_suffix().unwrap();
+
+        assert_ne!(first.device_asset_id(), second.device_asset_id());
+        assert_eq!(
+            first.device_asset_id(),
+            format!(
+                "{CLIENT_NAME} - 20250128_054236 - {}",
+                &first.checksum().unwrap()[..8]
+            )
+        );
+    }
+
+    #[test]
+    fn filename_from_signature() {
+        let asset = Asset::try_from(File::open("./utils/garden.jpg").unwrap()).unwrap();
+        assert_eq!(asset.filename(), format!("{}.jpg", asset.device_asset_id()));
+        assert_eq!(asset.asset_type(), &AssetType::Image);
+
+        assert_eq!(
+            AssetType::from_signature(b"\0\0\0\x14ftypqt  "),
+            Some((AssetType::Video, "mov"))
+        );
+        assert_eq!(AssetType::from_signature(b"text"), None);
+    }
+
+    #[test]
+    fn thumbnail() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("GET", "/assets/41a3a296-7e86-4eb4-8e44-aead03344fc9/thumbnail?size=preview") => {
+                (200, "\u{FF}\u{D8}\u{FF}preview".to_string())
+            }
+            ("GET", "/assets/41a3a296-7e86-4eb4-8e44-aead03344fc9/thumbnail?size=thumbnail") => {
+                (404, String::new())
+            }
+            _ => (500, String::new()),
+        });
+        let mut asset = Asset::from_bytes(b"\xFF\xD8\xFFimage".to_vec(), "image.jpg".to_string());
+        assert!(matches!(
+            asset.thumbnail(&client, ThumbnailSize::Preview),
+            Err(ImmichError::InvalidId)
+        ));
+
+        asset.id = "41a3a296-7e86-4eb4-8e44-aead03344fc9".try_into().unwrap();
+        assert_eq!(
+            asset.thumbnail(&client, ThumbnailSize::Preview).unwrap(),
+            "\u{FF}\u{D8}\u{FF}preview".as_bytes()
+        );
+        assert!(matches!(
+            asset.thumbnail(&client, ThumbnailSize::default()),
+            Err(ImmichError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn checksum_read_error() {
+        let asset = Asset::from_path(std::env::temp_dir()).unwrap();
+        assert!(matches!(asset.checksum(), Err(ImmichError::IoAt { .. })));
+    }
+}
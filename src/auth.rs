@@ -1,4 +1,10 @@
+use std::fmt::Debug;
+
+use log::debug;
 use serde::Deserialize;
+use ureq::{json, Request};
+
+use crate::{ImmichError, ImmichResult};
 
 #[allow(non_snake_case)]
 #[derive(Deserialize)]
@@ -6,7 +12,53 @@ pub(super) struct Login {
     pub accessToken: String,
 }
 
-#[derive(Debug, Clone)]
+/// Email and password of a user, kept to renew the session
+#[derive(Clone)]
+pub(crate) struct Credentials {
+    email: String,
+    password: String,
+}
+
+impl Credentials {
+    pub fn new(email: &str, password: &str) -> Self {
+        Self {
+            email: email.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    /// Logs in with the credentials and returns the session cookie
+    pub fn login(&self, request: Request) -> ImmichResult<Authenticated> {
+        let response = request.send_json(json!({
+            "email": self.email,
+            "password": self.password,
+        }))?;
+
+        if response.status() == 201 {
+            let login: Login = response.into_json()?;
+            Ok(Authenticated::Cookie(format!(
+                "immich_access_token={}",
+                login.accessToken
+            )))
+        } else {
+            debug!(
+                "Login failed: [{}] {}",
+                response.status(),
+                response.into_string().unwrap_or_default()
+            );
+            Err(ImmichError::Auth)
+        }
+    }
+}
+
+impl Debug for Credentials {
+    /// Redacts the actual credentials
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Credentials(***)")
+    }
+}
+
+#[derive(Clone)]
 pub(crate) enum Authenticated {
     Cookie(String),
     ApiKey(String),
@@ -20,3 +72,30 @@ impl Authenticated {
         }
     }
 }
+
+impl Debug for Authenticated {
+    /// Redacts the actual credentials
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Authenticated::Cookie(_) => write!(f, "Cookie(***)"),
+            Authenticated::ApiKey(_) => write!(f, "ApiKey(***)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_hides_secrets() {
+        let auth = Authenticated::ApiKey("s3cr3t".to_string());
+        assert_eq!(format!("{auth:?}"), "ApiKey(***)");
+
+        let auth = Authenticated::Cookie("immich_access_token=s3cr3t".to_string());
+        assert_eq!(format!("{auth:?}"), "Cookie(***)");
+
+        let credentials = Credentials::new("email@somewhere", "s3cr3t");
+        assert_eq!(format!("{credentials:?}"), "Credentials(***)");
+    }
+}
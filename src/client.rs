@@ -1,25 +1,36 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Display};
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 use crossbeam_channel::{unbounded, Sender};
-use ureq::Request;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use ureq::{Agent, MiddlewareNext, Request, Response};
 
 use crate::album::Albums;
 use crate::api::bulk_check::BulkUploadCheck;
-use crate::api::requests::MovedAsset;
-use crate::api::upload::{ParallelUpload, Uploaded};
-use crate::asset::Asset;
+use crate::api::requests::{
+    CreateStack, DeleteAssets, MovedAsset, SearchResponse, SearchTrashed, UpdateAssets,
+};
+use crate::api::upload::{ParallelUpload, UploadSummary, Uploaded};
+use crate::asset::{Asset, RemoteAsset};
 use crate::host::Host;
+use crate::search::{Page, SearchQuery};
 use crate::url::Url;
-use crate::utils::DEFAULT_HEADERS;
-use crate::{Album, ImmichError, ImmichResult, User};
+use crate::utils::{DEFAULT_HEADERS, USER_AGENT};
+use crate::{Album, AssetId, ImmichError, ImmichResult, MediaTypes, Stack, Statistics, User};
 
-use crate::auth::Authenticated;
+use crate::auth::{Authenticated, Credentials};
 
 pub(crate) trait ImmichClient: Sized {
     fn add_default_header(self) -> Self {
         self
     }
 
+    fn add_headers(self, headers: &[(String, String)]) -> Self;
+
     fn auth(self, auth: &Authenticated) -> Self;
 }
 
@@ -32,6 +43,14 @@ impl ImmichClient for Request {
         request
     }
 
+    fn add_headers(self, headers: &[(String, String)]) -> Self {
+        let mut request = self;
+        for (header, value) in headers {
+            request = request.set(header, value);
+        }
+        request
+    }
+
     fn auth(self, auth: &Authenticated) -> Self {
         let header = auth.header();
         self.set(header.0, header.1)
@@ -39,15 +58,41 @@ impl ImmichClient for Request {
 }
 
 /// Client to interact with the Immich remote server
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Client {
     url: Url,
     auth: Authenticated,
+    credentials: Option<Credentials>,
+    headers: Vec<(String, String)>,
+    album_cache: Option<Arc<Mutex<Option<Albums>>>>,
+    local_dedup: bool,
+    verify_uploads: bool,
+    validate_uploads: bool,
+    upload_buffer: Option<usize>,
+    media_types: Arc<Mutex<Option<MediaTypes>>>,
+    agent: Agent,
+}
+
+impl Debug for Client {
+    /// Shows the server URL, but redacts the credentials
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("url", &self.url.as_str())
+            .field("auth", &self.auth)
+            .finish()
+    }
 }
 
 impl Client {
     /// Connect to the Immich server with email and password authentication
     ///
+    /// # Note
+    ///
+    /// The password login of Immich has no second factor. Servers that enforce multi-factor
+    /// authentication do so via OAuth, which can't be used here. Use an API key with
+    /// [`Client::with_key`] instead, or log in with the OAuth provider and pass the session
+    /// token to [`Client::with_access_token`].
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -77,63 +122,994 @@ impl Client {
     ///     "7F4BR7QLvGzyiqcIszQq1nZdvyqFY955yW9msrqyeD"
     /// );
     ///
-    /// assert!(client.is_ok());
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn with_key(url: &str, key: &str) -> ImmichResult<Self> {
+        Host::new(url)?.key(key)
+    }
+
+    /// Connect to the Immich server with an existing session access token
+    ///
+    /// Use this method if you already obtained an `accessToken`, e.g. from an external
+    /// OAuth login flow. The token is validated with the server.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_access_token(
+    ///     "https://immich-web-url/api",
+    ///     "hOqGYf7TmiNvmXX2P4Hhw2pxh8fa3hmYUd5L6ZVic"
+    /// );
+    ///
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn with_access_token(url: &str, token: &str) -> ImmichResult<Self> {
+        Host::new(url)?.access_token(token)
+    }
+
+    /// Configure the connection to the Immich server before authenticating
+    ///
+    /// Use the [`ClientBuilder`] if the server requires additional headers on every request,
+    /// e.g. because it is behind an authenticating proxy.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::builder("https://immich-web-url/api")
+    ///     .unwrap()
+    ///     .header("CF-Access-Client-Id", "my-client-id.access")
+    ///     .header("CF-Access-Client-Secret", "my-client-secret")
+    ///     .key("7F4BR7QLvGzyiqcIszQq1nZdvyqFY955yW9msrqyeD");
+    ///
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn builder(url: &str) -> ImmichResult<ClientBuilder> {
+        Ok(ClientBuilder {
+            host: Host::new(url)?,
+        })
+    }
+
+    pub(crate) fn new(
+        url: Url,
+        auth: Authenticated,
+        headers: Vec<(String, String)>,
+        agent: Agent,
+    ) -> Self {
+        Self {
+            url,
+            auth,
+            credentials: None,
+            headers,
+            album_cache: None,
+            local_dedup: false,
+            verify_uploads: false,
+            validate_uploads: false,
+            upload_buffer: None,
+            media_types: Arc::new(Mutex::new(None)),
+            agent,
+        }
+    }
+
+    /// Creates a client that never connects to a server, for testing integrations
+    ///
+    /// Every request is passed to `handler` with the HTTP method and the path relative
+    /// to the API url, e.g. `("GET", "/albums")`. The handler returns the status code and
+    /// the JSON body of the response. Status codes `>= 400` are reported as
+    /// [`ImmichError::Status`], the same as responses of a real server. A status code
+    /// of `0` simulates a server that can't be reached and is reported as
+    /// [`ImmichError::Transport`].
+    ///
+    /// The request body is not passed to the handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::Client;
+    ///
+    /// let client = Client::mock(|method, path| match (method, path) {
+    ///     ("GET", "/users") => (200, r#"[{
+    ///         "id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+    ///         "email": "email@somewhere",
+    ///         "name": "Username"
+    ///     }]"#.to_string()),
+    ///     _ => (404, String::new()),
+    /// });
+    ///
+    /// let users = client.users().unwrap();
+    /// assert_eq!(users[0].name(), "Username");
+    /// assert!(client.albums().is_err());
+    /// ```
+    // The size of the error type is defined by `ureq`
+    #[allow(clippy::result_large_err)]
+    pub fn mock<F>(handler: F) -> Self
+    where
+        F: Fn(&str, &str) -> (u16, String) + Send + Sync + 'static,
+    {
+        let url = Url::try_from("http://immich.mock/api").expect("The mock url is valid");
+        let base = url.as_str().to_string();
+        let agent = ureq::builder()
+            .middleware(move |request: Request, _next: MiddlewareNext| {
+                let path = request.url().strip_prefix(&base).unwrap_or(request.url());
+                let (status, body) = handler(request.method(), path);
+                if status == 0 {
+                    return Err(std::io::Error::from(std::io::ErrorKind::ConnectionRefused).into());
+                }
+                ureq::Response::new(status, "Mocked response", &body)
+            })
+            .build();
+        Self::new(
+            url,
+            Authenticated::ApiKey("mock".to_string()),
+            Vec::new(),
+            agent,
+        )
+    }
+
+    pub(crate) fn with_credentials(self, credentials: Credentials) -> Self {
+        Self {
+            credentials: Some(credentials),
+            ..self
+        }
+    }
+
+    /// Renews the session of the client
+    ///
+    /// Session access tokens expire, so long running services should refresh the
+    /// session from time to time to not fail with [`ImmichError::Auth`] in the middle
+    /// of an import.
+    ///
+    /// - Clients created by [`ClientBuilder::keep_credentials`] and [`ClientBuilder::email`]
+    ///   log in again and use the new access token
+    /// - Clients with an API key don't have a session and nothing is done
+    /// - All other clients can't log in again and only check that their access token is
+    ///   still valid
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let mut client = Client::builder("https://immich-web-url/api")
+    ///     .unwrap()
+    ///     .keep_credentials()
+    ///     .email("email@somewhere", "s3cr3tpassword")
+    ///     .unwrap();
+    ///
+    /// // ... hours later
+    /// client.refresh().unwrap();
+    /// ```
+    pub fn refresh(&mut self) -> ImmichResult<()> {
+        match (&self.auth, &self.credentials) {
+            (Authenticated::ApiKey(_), _) => Ok(()),
+            (Authenticated::Cookie(_), Some(credentials)) => {
+                let request = self
+                    .agent
+                    .post(&self.url.add_path("/auth/login"))
+                    .add_default_header()
+                    .add_headers(&self.headers);
+                self.auth = credentials.login(request)?;
+                Ok(())
+            }
+            (Authenticated::Cookie(_), None) => self.check_connection(),
+        }
+    }
+
+    /// Enables caching of the album list
+    ///
+    /// [`Album::get_or_create`] fetches the full album list from the server on every call.
+    /// With the cache enabled, the list is only fetched once and then kept up to date with
+    /// all albums created via [`Album::get_or_create`]. The cache is shared between all
+    /// clones of the client.
+    ///
+    /// Albums created or deleted by other means are not reflected in the cache. Use
+    /// [`Client::invalidate_album_cache`] to force re-fetching the album list.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap().with_album_cache();
+    ///
+    /// for name in ["Holiday", "Wedding", "Birthday"] {
+    ///     let album = Album::get_or_create(&client, name.to_string()).unwrap();
+    ///     println!("{}: {}", album.album().name(), album.created());
+    /// }
+    /// ```
+    pub fn with_album_cache(mut self) -> Self {
+        if self.album_cache.is_none() {
+            self.album_cache = Some(Arc::new(Mutex::new(None)));
+        }
+        self
+    }
+
+    /// Skips uploading files with the same content as a previously uploaded file
+    ///
+    /// By default, all assets are uploaded and the Immich server detects duplicates.
+    /// With local deduplication, the [`Asset::checksum`] of every asset is calculated
+    /// before the upload and assets whose data was already uploaded in the same run
+    /// (e.g. copies of a file with a different name) are not uploaded again. This saves
+    /// bandwidth for folders with many redundant copies.
+    ///
+    /// Skipped assets are reported as [`Status::Duplicate`](crate::upload::Status::Duplicate)
+    /// with the id of the first asset, after all other assets were uploaded.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap().with_local_dedup();
+    ///
+    /// let assets = std::fs::read_dir("/path/to/folder").unwrap()
+    ///     .filter_map(|entry| Asset::try_from(entry.unwrap().path()).ok());
+    ///
+    /// for uploaded in client.upload(5, assets, None).unwrap() {
+    ///     println!("{}: {}", uploaded.device_asset_id(), uploaded.status());
+    /// }
+    /// ```
+    pub fn with_local_dedup(mut self) -> Self {
+        self.local_dedup = true;
+        self
+    }
+
+    pub(crate) fn local_dedup(&self) -> bool {
+        self.local_dedup
+    }
+
+    /// Verifies the checksum of every uploaded asset
+    ///
+    /// After each upload, the checksum of the asset on the server is compared to the checksum
+    /// of the local data (see [`Asset::verify`]). Mismatches are reported as
+    /// [`Status::Failure`](crate::upload::Status::Failure) and the asset is permanently deleted
+    /// from the server again.
+    ///
+    /// This requires an additional request for every newly created asset.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap().with_upload_verification();
+    ///
+    /// let assets = std::fs::read_dir("/path/to/folder").unwrap()
+    ///     .filter_map(|entry| Asset::try_from(entry.unwrap().path()).ok());
+    ///
+    /// for uploaded in client.upload(5, assets, None).unwrap() {
+    ///     println!("{}: {}", uploaded.device_asset_id(), uploaded.status());
+    /// }
+    /// ```
+    pub fn with_upload_verification(mut self) -> Self {
+        self.verify_uploads = true;
+        self
+    }
+
+    pub(crate) fn verify_uploads(&self) -> bool {
+        self.verify_uploads
+    }
+
+    /// Checks the media data of every asset before uploading it
+    ///
+    /// Assets that are empty or clearly don't contain their type of media (see
+    /// [`Asset::validate`]) are reported as [`Status::Failure`](crate::upload::Status::Failure)
+    /// by parallel uploads, e.g. [`Client::upload`], and are not uploaded.
+    ///
+    /// The check only knows the signatures of common formats, so it can reject valid assets
+    /// of rare formats or with a wrong file extension.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap().with_upload_validation();
+    ///
+    /// let assets = std::fs::read_dir("/path/to/folder").unwrap()
+    ///     .filter_map(|entry| Asset::try_from(entry.unwrap().path()).ok());
+    ///
+    /// for uploaded in client.upload(5, assets, None).unwrap() {
+    ///     println!("{}: {}", uploaded.device_asset_id(), uploaded.status());
+    /// }
+    /// ```
+    pub fn with_upload_validation(mut self) -> Self {
+        self.validate_uploads = true;
+        self
+    }
+
+    pub(crate) fn validate_uploads(&self) -> bool {
+        self.validate_uploads
+    }
+
+    /// Sets how many assets are read ahead and wait for a free upload thread
+    ///
+    /// During parallel uploads (e.g. [`Client::upload`]), the assets are read from the input
+    /// iterator in advance, so that every upload thread can start the next upload right away.
+    /// By default, twice as many assets as upload threads are buffered. Assets created with
+    /// [`Asset::try_from::<PathBuf>`](Asset::try_from) hold the full file in memory, so the
+    /// buffer can take up a lot of memory for large videos.
+    ///
+    /// A smaller `capacity` lowers the memory usage, at the cost of some throughput because
+    /// the upload threads might have to wait for the next asset. With a `capacity` of `0`,
+    /// an asset is only read when an upload thread is ready to upload it.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap().with_upload_buffer(1);
+    ///
+    /// let assets = std::fs::read_dir("/path/to/videos").unwrap()
+    ///     .filter_map(|entry| Asset::try_from(entry.unwrap().path()).ok());
+    ///
+    /// for uploaded in client.upload(2, assets, None).unwrap() {
+    ///     println!("{}: {}", uploaded.device_asset_id(), uploaded.status());
+    /// }
+    /// ```
+    pub fn with_upload_buffer(mut self, capacity: usize) -> Self {
+        self.upload_buffer = Some(capacity);
+        self
+    }
+
+    pub(crate) fn upload_buffer(&self) -> Option<usize> {
+        self.upload_buffer
+    }
+
+    /// Clears the cached album list, if caching is enabled
+    pub fn invalidate_album_cache(&self) {
+        if let Some(cache) = &self.album_cache {
+            if let Ok(mut cache) = cache.lock() {
+                *cache = None;
+            }
+        }
+    }
+
+    /// Returns the first album with the given name, using the cache if enabled
+    pub(crate) fn find_album(&self, name: &str) -> ImmichResult<Option<Album>> {
+        match &self.album_cache {
+            Some(cache) => {
+                let mut cache = cache.lock().map_err(|_| ImmichError::Multithread)?;
+                if cache.is_none() {
+                    *cache = Some(self.albums()?);
+                }
+                Ok(cache
+                    .as_ref()
+                    .and_then(|albums| albums.find_by_name(name))
+                    .cloned())
+            }
+            None => Ok(self
+                .albums()?
+                .into_iter()
+                .find(|album| album.name() == name)),
+        }
+    }
+
+    /// Adds a newly created album to the cache, if enabled
+    pub(crate) fn cache_album(&self, album: &Album) {
+        if let Some(cache) = &self.album_cache {
+            if let Ok(mut cache) = cache.lock() {
+                if let Some(albums) = cache.as_mut() {
+                    albums.push(album.clone());
+                }
+            }
+        }
+    }
+
+    /// Checks that the server can be reached and accepts the credentials of the client
+    ///
+    /// # Errors
+    ///
+    /// - [`ImmichError::Transport`]: The server can't be reached, e.g. due to a wrong URL
+    /// - [`ImmichError::Auth`]: The server rejected the credentials
+    /// - [`ImmichError::Status`]: The server responded with any other unexpected status
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Client, ImmichError};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// match client.check_connection() {
+    ///     Ok(()) => println!("Connected"),
+    ///     Err(ImmichError::Transport(..)) => println!("Check the server URL"),
+    ///     Err(ImmichError::Auth) => println!("Check the credentials"),
+    ///     Err(err) => println!("Unexpected error: {err}"),
+    /// }
+    /// ```
+    pub fn check_connection(&self) -> ImmichResult<()> {
+        match Self::read_empty(self.post("/auth/validateToken").call()) {
+            Err(ImmichError::Status(401, _)) => Err(ImmichError::Auth),
+            result => result,
+        }
+    }
+
+    pub(crate) fn user(&self) -> ImmichResult<User> {
+        self.get_json("/users/me")
+    }
+
+    /// Returns the response if it is successful
+    ///
+    /// Responses with error status codes are returned as [`ImmichError::Status`]
+    /// (or [`ImmichError::NotFound`]) with the response body as message.
+    pub(crate) fn read_response(response: Result<Response, ureq::Error>) -> ImmichResult<Response> {
+        match response {
+            Ok(response) if (200..300).contains(&response.status()) => Ok(response),
+            Ok(response) => Err(ImmichError::from_status(
+                response.status(),
+                response.into_string()?,
+            )),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Parses the JSON body of a successful response, see [`Client::read_response`]
+    pub(crate) fn read_json<T: DeserializeOwned>(
+        response: Result<Response, ureq::Error>,
+    ) -> ImmichResult<T> {
+        Ok(Self::read_response(response)?.into_json()?)
+    }
+
+    /// Checks that a response without a body is successful, see [`Client::read_response`]
+    pub(crate) fn read_empty(response: Result<Response, ureq::Error>) -> ImmichResult<()> {
+        Self::read_response(response).map(|_| ())
+    }
+
+    pub(crate) fn get_json<T: DeserializeOwned>(&self, url: &str) -> ImmichResult<T> {
+        Self::read_json(self.get(url).call())
+    }
+
+    pub(crate) fn post_json<T: DeserializeOwned, B: Serialize>(
+        &self,
+        url: &str,
+        body: B,
+    ) -> ImmichResult<T> {
+        Self::read_json(self.post(url).send_json(body))
+    }
+
+    pub(crate) fn put_json<T: DeserializeOwned, B: Serialize>(
+        &self,
+        url: &str,
+        body: B,
+    ) -> ImmichResult<T> {
+        Self::read_json(self.put(url).send_json(body))
+    }
+
+    pub(crate) fn get(&self, url: &str) -> Request {
+        self.request("GET", url)
+    }
+
+    pub(crate) fn post(&self, url: &str) -> Request {
+        self.request("POST", url)
+    }
+
+    pub(crate) fn put(&self, url: &str) -> Request {
+        self.request("PUT", url)
+    }
+
+    pub(crate) fn patch(&self, url: &str) -> Request {
+        self.request("PATCH", url)
+    }
+
+    pub(crate) fn delete(&self, url: &str) -> Request {
+        self.request("DELETE", url)
+    }
+
+    fn request(&self, method: &str, url: &str) -> Request {
+        let url = self.url.add_path(url);
+        self.agent
+            .request(method, &url)
+            .add_default_header()
+            .add_headers(&self.headers)
+            .auth(&self.auth)
+    }
+
+    /// Returns a list of all albums on the server
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// for album in client.albums().unwrap() {
+    ///     println!("{}: {} assets", album.name(), album.len());
+    /// }
+    /// ```
+    pub fn albums(&self) -> ImmichResult<Albums> {
+        self.get_json("/albums")
+    }
+
+    /// Creates a new album with images and videos that already exist on the server
+    ///
+    /// This combines [`Album::new`] and [`Album::add_assets`]. The album is created even if
+    /// it has the same name as an existing album. Assets that can't be added, e.g. because
+    /// they don't exist, are silently skipped. The returned album counts only the assets
+    /// that were added.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the album can't be created or the assets can't be added. In the latter
+    /// case, the empty album remains on the server.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let albums = client.albums().unwrap();
+    /// let holiday = albums.find_by_name("Holiday").unwrap();
+    /// let favorites = holiday
+    ///     .assets(&client)
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .filter(|asset| asset.favorite())
+    ///     .map(|asset| asset.id().clone());
+    ///
+    /// let album = client
+    ///     .create_album_with("Holiday favorites".to_string(), favorites)
+    ///     .unwrap();
+    /// println!("{}: {} favorites", album.name(), album.len());
+    /// ```
+    pub fn create_album_with<I: Iterator<Item = AssetId>>(
+        &self,
+        name: String,
+        asset_ids: I,
+    ) -> ImmichResult<Album> {
+        let mut album = Album::new(self, name)?;
+        let added = album
+            .add_assets(self, asset_ids)?
+            .iter()
+            .filter(|moved| moved.success())
+            .count();
+        album.set_len(added);
+        self.cache_album(&album);
+        Ok(album)
+    }
+
+    /// Returns true if an album with the given name exists
+    ///
+    /// Immich can't search albums by name, so the full album list is fetched, or read from
+    /// the cache if enabled (see [`Client::with_album_cache`]). Use this to check if
+    /// [`Album::get_or_create`] would create a new album.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// if !client.album_exists_by_name("Holiday").unwrap() {
+    ///     println!("The album Holiday will be created");
+    /// }
+    /// ```
+    pub fn album_exists_by_name(&self, name: &str) -> ImmichResult<bool> {
+        Ok(self.find_album(name)?.is_some())
+    }
+
+    /// Returns true if an album with the given id exists
+    ///
+    /// Only the album itself is fetched from the server, without the list of its images
+    /// and videos. Albums the user can't access are reported as not existing.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{AssetId, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let id = AssetId::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap();
+    /// assert!(client.album_exists_by_id(&id).unwrap());
+    /// ```
+    pub fn album_exists_by_id(&self, id: &AssetId) -> ImmichResult<bool> {
+        if !id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        let response = self
+            .get(&format!("/albums/{id}"))
+            .query("withoutAssets", "true")
+            .call();
+        match Client::read_empty(response) {
+            Ok(()) => Ok(true),
+            Err(ImmichError::NotFound(_)) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Returns the image or video with the given id
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{AssetId, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let id = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+    /// let asset = client.asset(&id).unwrap();
+    /// println!("{} [{:?}]", asset.original_file_name(), asset.asset_type());
+    /// ```
+    pub fn asset(&self, id: &AssetId) -> ImmichResult<RemoteAsset> {
+        if !id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        self.get_json(&format!("/assets/{id}"))
+    }
+
+    /// Returns the image and video file types that the server accepts
+    ///
+    /// Use this to skip files that the server would reject anyway, before uploading them.
+    /// The media types are fetched only once and then cached, also for all clones
+    /// of the client.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let media_types = client.supported_media_types().unwrap();
+    /// let assets = std::fs::read_dir("/path/to/folder").unwrap()
+    ///     .map(|entry| entry.unwrap().path())
+    ///     .filter(|path| media_types.supports(&path.to_string_lossy()))
+    ///     .filter_map(|path| Asset::try_from(path).ok());
+    ///
+    /// client.upload(5, assets, None).unwrap();
+    /// ```
+    pub fn supported_media_types(&self) -> ImmichResult<MediaTypes> {
+        let mut cache = self
+            .media_types
+            .lock()
+            .map_err(|_| ImmichError::Multithread)?;
+        if let Some(media_types) = cache.as_ref() {
+            return Ok(media_types.clone());
+        }
+        let media_types: MediaTypes = self.get_json("/server/media-types")?;
+        *cache = Some(media_types.clone());
+        Ok(media_types)
+    }
+
+    /// Returns a list of all users on the server
+    ///
+    /// # Errors
+    ///
+    /// If the user or API key is not permitted to read other users, the server's
+    /// response is returned as [`ImmichError::Status`] with status code `403`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// for user in client.users().unwrap() {
+    ///     println!("{user}");
+    /// }
+    /// ```
+    pub fn users(&self) -> ImmichResult<Vec<User>> {
+        self.get_json("/users")
+    }
+
+    /// Returns the user with the given id
+    ///
+    /// # Errors
+    ///
+    /// If the user or API key is not permitted to read other users, the server's
+    /// response is returned as [`ImmichError::Status`] with status code `403`.
+    /// An id that is not formatted like a UUID returns [`ImmichError::InvalidId`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{AssetId, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let id = AssetId::try_from("6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e").unwrap();
+    /// let user = client.user_by_id(&id).unwrap();
+    /// println!("{}", user.email());
+    /// ```
+    pub fn user_by_id(&self, id: &AssetId) -> ImmichResult<User> {
+        if !id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        self.get_json(&format!("/users/{id}"))
+    }
+
+    /// Moves all images and videos of the `merge` albums to the `keep` album
+    ///
+    /// Use this method to consolidate several albums with the same name. The `merge` albums
+    /// are deleted once all their assets were added to `keep`. Assets that are already in `keep`
+    /// are reported as success with [`AssetMoveError::Duplicate`](crate::AssetMoveError::Duplicate).
+    /// If any other asset cannot be added, the `merge` album is kept.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let mut albums: Vec<Album> = client
+    ///     .albums()
+    ///     .unwrap()
+    ///     .into_iter()
+    ///     .filter(|album| album.name() == "Holiday")
+    ///     .collect();
+    ///
+    /// if let Some(keep) = albums.pop() {
+    ///     client.merge_albums(&keep, &albums).unwrap();
+    /// }
+    /// ```
+    pub fn merge_albums(&self, keep: &Album, merge: &[Album]) -> ImmichResult<Vec<MovedAsset>> {
+        let mut moved_assets = Vec::new();
+        for album in merge.iter().filter(|album| album.id() != keep.id()) {
+            let ids = album.asset_ids(self)?;
+            let moved = if ids.is_empty() {
+                Vec::new()
+            } else {
+                keep.add_assets(self, ids.into_iter())?
+            };
+            if moved.iter().all(|asset| asset.success()) {
+                album.delete(self)?;
+            }
+            moved_assets.extend(moved);
+        }
+        self.invalidate_album_cache();
+        Ok(moved_assets)
+    }
+
+    /// Returns the number of images and videos of the user on the server
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let stats = client.statistics().unwrap();
+    /// println!(
+    ///     "Server now holds {} photos and {} videos",
+    ///     stats.images(),
+    ///     stats.videos()
+    /// );
+    /// ```
+    pub fn statistics(&self) -> ImmichResult<Statistics> {
+        self.get_json("/assets/statistics")
+    }
+
+    /// Returns one page of the images and videos that match the `query`
+    ///
+    /// The returned assets don't contain the actual image or video data. Pass the
+    /// cursor of [`Page::next`] to [`SearchQuery::cursor`] to fetch the next page.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Client, SearchQuery};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let page = client.search(SearchQuery::new().text("sunset")).unwrap();
+    /// for asset in page.items() {
+    ///     println!("{}: {}", asset.id(), asset.device_asset_id());
+    /// }
+    /// ```
+    pub fn search(&self, query: SearchQuery) -> ImmichResult<Page<Asset>> {
+        let response: SearchResponse = self.post_json(query.path(), &query)?;
+        response.assets.try_into()
+    }
+
+    /// Returns all images and videos of the user
+    ///
+    /// The assets are fetched page by page from the server while iterating. The
+    /// returned assets don't contain the actual image or video data.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// for asset in client.all_assets() {
+    ///     let asset = asset.unwrap();
+    ///     println!("{}: {}", asset.id(), asset.device_asset_id());
+    /// }
+    /// ```
+    pub fn all_assets(&self) -> impl Iterator<Item = ImmichResult<Asset>> + '_ {
+        let mut query = Some(SearchQuery::new());
+        let mut items = Vec::new().into_iter();
+        std::iter::from_fn(move || loop {
+            if let Some(asset) = items.next() {
+                return Some(Ok(asset));
+            }
+            let current = query.take()?;
+            match self.search(current.clone()) {
+                Ok(page) => {
+                    let (assets, next) = page.into_parts();
+                    items = assets.into_iter();
+                    query = next.map(|cursor| current.cursor(cursor));
+                }
+                Err(err) => return Some(Err(err)),
+            }
+        })
+    }
+
+    /// Returns all assets that are currently in the trash
+    ///
+    /// The returned assets don't contain the actual image or video data.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// for asset in client.trashed_assets().unwrap() {
+    ///     println!("{}: {}", asset.id(), asset.device_asset_id());
+    /// }
+    /// ```
+    pub fn trashed_assets(&self) -> ImmichResult<Vec<Asset>> {
+        let mut assets = Vec::new();
+        let mut page = 1;
+        loop {
+            let response: SearchResponse =
+                self.post_json("/search/metadata", SearchTrashed::page(page))?;
+            assets.extend(response.assets.items);
+            match response.assets.nextPage {
+                Some(next) => page = next.parse().map_err(|_| ImmichError::InvalidResponse)?,
+                None => return Ok(assets),
+            }
+        }
+    }
+
+    /// Permanently deletes all assets in the trash
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// client.empty_trash().unwrap();
+    /// ```
+    pub fn empty_trash(&self) -> ImmichResult<()> {
+        Self::read_empty(self.post("/trash/empty").call())
+    }
+
+    /// Archives or unarchives the assets on the Immich server
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let mut asset: Asset = std::path::PathBuf::from("/path/to/image").try_into().unwrap();
+    /// asset.upload(&client).unwrap();
+    ///
+    /// client.set_archived(&[asset.id().clone()], true).unwrap();
     /// ```
-    pub fn with_key(url: &str, key: &str) -> ImmichResult<Self> {
-        Host::new(url)?.key(key)
-    }
-
-    pub(crate) fn new(url: Url, auth: Authenticated) -> Self {
-        Self { url, auth }
-    }
-
-    pub(crate) fn check_auth(&self) -> bool {
-        match self.post("/auth/validateToken").call() {
-            Ok(response) => response.status() == 200,
-            Err(_) => false,
-        }
-    }
-
-    pub(crate) fn user(&self) -> ImmichResult<User> {
-        match self.get("/users/me").call() {
-            Ok(response) => {
-                if response.status() == 200 {
-                    Ok(response.into_json()?)
-                } else {
-                    Err(ImmichError::Status(
-                        response.status(),
-                        response.into_string()?,
-                    ))
-                }
-            }
-            Err(err) => Err(ImmichError::Transport(err.to_string())),
+    pub fn set_archived(&self, ids: &[AssetId], archived: bool) -> ImmichResult<()> {
+        if ids.is_empty() {
+            return Ok(());
         }
+        Self::read_empty(
+            self.put("/assets")
+                .send_json(UpdateAssets::archived(ids, archived)),
+        )
     }
 
-    pub(crate) fn get(&self, url: &str) -> Request {
-        ureq::get(&self.url.add_path(url))
-            .add_default_header()
-            .auth(&self.auth)
-    }
-
-    pub(crate) fn post(&self, url: &str) -> Request {
-        ureq::post(&self.url.add_path(url))
-            .add_default_header()
-            .auth(&self.auth)
-    }
-
-    pub(crate) fn put(&self, url: &str) -> Request {
-        ureq::put(&self.url.add_path(url))
-            .add_default_header()
-            .auth(&self.auth)
-    }
-
-    /// Returns a list of all albums on the server
+    /// Groups the images and videos into a stack, e.g. burst photos or edited versions
+    ///
+    /// The first asset is the primary asset, which represents the stack in the timeline.
+    /// Assets that are already part of another stack are moved to the new stack.
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use immich::{Asset, Client};
+    /// use immich::{AssetId, Client};
     ///
     /// let client = Client::with_email(
     ///     "https://immich-web-url/api",
@@ -141,15 +1117,26 @@ impl Client {
     ///     "s3cr3tpassword"
     /// ).unwrap();
     ///
-    /// for album in client.albums().unwrap() {
-    ///     println!("{}: {} assets", album.name(), album.len());
-    /// }
+    /// let edited = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+    /// let original = AssetId::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap();
+    /// let stack = client.create_stack(&[edited, original]).unwrap();
+    /// println!("{}: primary asset {}", stack.id(), stack.primary_asset_id());
     /// ```
-    pub fn albums(&self) -> ImmichResult<Albums> {
-        self.get("/albums")
-            .call()?
-            .into_json()
-            .map_err(|err| ImmichError::Io { source: err })
+    pub fn create_stack(&self, asset_ids: &[AssetId]) -> ImmichResult<Stack> {
+        self.post_json("/stacks", CreateStack::from(asset_ids))
+    }
+
+    /// Moves the assets to the trash of the Immich server
+    ///
+    /// Use [`Asset::restore`] to restore them again.
+    pub fn trash_assets(&self, ids: &[AssetId]) -> ImmichResult<()> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+        Self::read_empty(
+            self.delete("/assets")
+                .send_json(DeleteAssets::new(ids.iter().cloned(), false)),
+        )
     }
 
     /// Checks if images or videos are already in the database
@@ -182,6 +1169,28 @@ impl Client {
     /// This methods blocks until all assets are uploaded. If you want to receive progress upate
     /// you can pass a `crossbeam_channel` that is used to send info about each uploaded asset.
     ///
+    /// The progress channel receives the results in the order the uploads finish, but the
+    /// returned `Vec` is always in the same order as the input `assets`.
+    ///
+    /// The uploaded assets are always owned by the user the client is authenticated as.
+    /// Immich does not offer a way for admins to upload on behalf of other users. To upload
+    /// into the accounts of several users, e.g. to migrate their Takeout archives, create one
+    /// client per user, for example with [`Client::with_key`] and an API key of each user.
+    ///
+    /// # Memory usage
+    ///
+    /// The assets are pulled from the iterator only as fast as they are uploaded, so the
+    /// memory usage does not grow with the number of assets. At most `concurrency` assets
+    /// are uploaded at the same time and [`Client::with_upload_buffer`] more assets wait in
+    /// the queue. With assets from [`Asset::try_from::<PathBuf>`](Asset::try_from), which hold
+    /// the full file in memory, the peak memory usage is roughly
+    /// `(concurrency + upload buffer) * size of the largest assets`.
+    ///
+    /// The media data is streamed into the upload request and not copied. On machines with
+    /// little memory, use assets from [`Asset::from_path`] instead. They only keep the path
+    /// and metadata in memory and are read from disk in small chunks while uploading, so even
+    /// large videos don't need much memory, independent of the concurrency and buffer size.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -218,6 +1227,25 @@ impl Client {
     /// }
     /// ```
     ///
+    /// Upload large videos with little memory usage, e.g. from a Raspberry Pi
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap().with_upload_buffer(0);
+    ///
+    /// let assets = std::fs::read_dir("/path/to/videos").unwrap()
+    ///     .filter_map(|entry| Asset::from_path(entry.unwrap().path()).ok());
+    ///
+    /// for uploaded in client.upload(2, assets, None).unwrap() {
+    ///     println!("{}: {}", uploaded.device_asset_id(), uploaded.status());
+    /// }
+    /// ```
+    ///
     /// Specify a `crossbeam_channel` to print live progress update of the upload
     ///
     /// ```no_run
@@ -264,6 +1292,88 @@ impl Client {
         ParallelUpload::new(upload_concurrency).post(self, assets, progress_channel)
     }
 
+    /// Uploads many images or videos in parallel and reports assets that could not be created
+    ///
+    /// This method works like [`Client::upload`], but accepts an iterator of `Result`s.
+    /// Every `Err` is reported as a [`Status::Failure`](crate::upload::Status::Failure)
+    /// without a device asset id, and its message is available via [`Uploaded::error`].
+    /// This way, the number of results matches the number of files you intended to upload.
+    ///
+    /// Include the filename in the error message to identify the affected files.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    /// use immich::upload::Status;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let path = "/path/to/folder/with/images or videos";
+    ///
+    /// let asset_iterator = std::fs::read_dir(path).unwrap()
+    ///     .filter_map(|entry| {
+    ///         let path = entry.unwrap().path();
+    ///         if path.is_dir() {
+    ///             None
+    ///         } else {
+    ///             Some(Asset::try_from(path.clone())
+    ///                 .map_err(|err| format!("{}: {err}", path.display())))
+    ///         }
+    ///     });
+    ///
+    /// let result = client.upload_fallible(5, asset_iterator, None)
+    ///     .expect("Parallel upload works");
+    ///
+    /// for entry in result.iter().filter(|entry| entry.status() == &Status::Failure) {
+    ///     println!("{}", entry.error().unwrap_or_default());
+    /// }
+    /// ```
+    pub fn upload_fallible<E: Display, I: Iterator<Item = Result<Asset, E>>>(
+        &self,
+        upload_concurrency: usize,
+        assets: I,
+        progress_channel: Option<Sender<Uploaded>>,
+    ) -> ImmichResult<Vec<Uploaded>> {
+        ParallelUpload::new(upload_concurrency).post_fallible(self, assets, progress_channel)
+    }
+
+    /// Uploads many images or videos in parallel and returns a summary of the results
+    ///
+    /// Works like [`Client::upload`], but counts the created, duplicate and failed uploads.
+    /// Use [`Client::upload`] if you need the details of every single upload.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let assets = std::fs::read_dir("/path/to/folder").unwrap()
+    ///     .filter_map(|entry| Asset::try_from(entry.unwrap().path()).ok());
+    ///
+    /// let summary = client.upload_summarized(5, assets, None).unwrap();
+    /// println!("{summary}");
+    /// ```
+    pub fn upload_summarized<I: Iterator<Item = Asset>>(
+        &self,
+        upload_concurrency: usize,
+        assets: I,
+        progress_channel: Option<Sender<Uploaded>>,
+    ) -> ImmichResult<UploadSummary> {
+        self.upload(upload_concurrency, assets, progress_channel)
+            .map(UploadSummary::from)
+    }
+
     pub(crate) fn auth(&self) -> &Authenticated {
         &self.auth
     }
@@ -291,7 +1401,7 @@ impl Client {
     ///
     /// let path = "/path/to/folder/with/images or videos";
     ///
-    /// let album = Album::get_or_create(&client, "My Album".to_string()).unwrap();
+    /// let album = Album::get_or_create(&client, "My Album".to_string()).unwrap().into();
     ///
     /// let asset_iterator = std::fs::read_dir(path).unwrap()
     ///     .filter_map(|entry| {
@@ -343,6 +1453,583 @@ impl Client {
             self.upload(upload_concurrency, assets, None)?
         };
 
-        album.add_uploaded(self, results)
+        album.add_uploaded(self, &results)
+    }
+
+    /// Uploads assets and adds them to the album with the given name
+    ///
+    /// The album is created if it does not exist yet (see [`Album::get_or_create`]).
+    /// Otherwise, this method works like [`Client::upload_to_album`].
+    ///
+    /// # Errors
+    ///
+    /// Fails right away if the album can't be found or created
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let path = "/path/to/folder/with/images or videos";
+    ///
+    /// let asset_iterator = std::fs::read_dir(path).unwrap()
+    ///     .filter_map(|entry| {
+    ///         let path = entry.unwrap().path();
+    ///         if path.is_dir() {
+    ///             None
+    ///         } else {
+    ///             Asset::try_from(path).ok()
+    ///         }
+    ///     });
+    ///
+    /// let result = client
+    ///     .upload_to_album_named(5, asset_iterator, "My Album".to_string(), None)
+    ///     .expect("All assets uploaded and added to album");
+    /// ```
+    pub fn upload_to_album_named<I: Iterator<Item = Asset>>(
+        &self,
+        upload_concurrency: usize,
+        assets: I,
+        album_name: String,
+        progress_channel: Option<Sender<Uploaded>>,
+    ) -> ImmichResult<Vec<MovedAsset>> {
+        let album = Album::get_or_create(self, album_name)?;
+        self.upload_to_album(upload_concurrency, assets, album.album(), progress_channel)
+    }
+
+    /// Uploads assets and adds each of them to its own list of albums
+    ///
+    /// Every asset comes with the names of the albums it should be added to. All assets
+    /// are uploaded in parallel first, then they are added to their albums with one request
+    /// per album. Albums that don't exist yet are created (see [`Album::get_or_create`]).
+    /// Assets without any album name are only uploaded and reported once without
+    /// [`MovedAsset::album_name`], successful if the upload succeeded.
+    ///
+    /// The returned [`MovedAsset`]s report the album they belong to in
+    /// [`MovedAsset::album_name`]. An asset that is added to several albums is reported
+    /// once per album.
+    ///
+    /// # Errors
+    ///
+    /// - The upload fails, e.g. due to threading issues: Fails right away
+    /// - An album can't be created or the assets can't be added to it: The assets of this
+    ///   album are reported as failed [`MovedAsset`]s
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    /// use immich::{Asset, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let assets = [
+    ///     ("/path/to/holiday.jpg", vec!["Holiday".to_string()]),
+    ///     ("/path/to/wedding.jpg", vec!["Wedding".to_string(), "Family".to_string()]),
+    /// ]
+    /// .into_iter()
+    /// .filter_map(|(path, albums)| Some((Asset::try_from(PathBuf::from(path)).ok()?, albums)));
+    ///
+    /// for moved in client.upload_with_albums(5, assets, None).unwrap() {
+    ///     println!("{}: {}", moved.album_name().unwrap_or_default(), moved.success());
+    /// }
+    /// ```
+    pub fn upload_with_albums<I: Iterator<Item = (Asset, Vec<String>)>>(
+        &self,
+        upload_concurrency: usize,
+        assets: I,
+        progress_channel: Option<Sender<Uploaded>>,
+    ) -> ImmichResult<Vec<MovedAsset>> {
+        let mut album_names: Vec<Vec<String>> = Vec::new();
+        let assets = assets.map(|(asset, albums)| {
+            album_names.push(albums);
+            asset
+        });
+        // the upload results are in the same order as the input assets
+        let results = self.upload(upload_concurrency, assets, progress_channel)?;
+
+        // reports an asset that is not part of an album
+        let not_moved = |uploaded: &Uploaded, success: bool| match uploaded.id() {
+            Some(id) => {
+                let mut moved = MovedAsset::new(id.clone(), success);
+                moved.set_upload_status(uploaded.status().clone());
+                moved
+            }
+            None => MovedAsset::from_failed_upload(),
+        };
+
+        let mut moved_assets: Vec<MovedAsset> = Vec::new();
+        let mut albums: HashMap<&str, Vec<Uploaded>> = HashMap::new();
+        for (uploaded, names) in results.into_iter().zip(&album_names) {
+            if names.is_empty() {
+                moved_assets.push(not_moved(&uploaded, true));
+            }
+            let names: HashSet<&str> = names.iter().map(String::as_str).collect();
+            for name in names {
+                albums.entry(name).or_default().push(uploaded.clone());
+            }
+        }
+
+        // all albums are checked, so it's worth fetching the album list only once
+        let client = &self.clone().with_album_cache();
+        for (name, uploaded) in albums {
+            let mut moved = Album::get_or_create(client, name.to_string())
+                .and_then(|album| album.album().add_uploaded(client, &uploaded))
+                .unwrap_or_else(|err| {
+                    warn!(
+                        "Failed to add {} assets to album {name}: {err}",
+                        uploaded.len()
+                    );
+                    uploaded
+                        .iter()
+                        .map(|uploaded| not_moved(uploaded, false))
+                        .collect()
+                });
+            for moved in moved.iter_mut() {
+                moved.set_album_name(name);
+            }
+            moved_assets.append(&mut moved);
+        }
+        Ok(moved_assets)
+    }
+}
+
+/// Builder to configure the connection to the Immich server
+///
+/// The builder is created with [`Client::builder`] and finished by authenticating
+/// with one of [`ClientBuilder::email`], [`ClientBuilder::key`] or
+/// [`ClientBuilder::access_token`].
+pub struct ClientBuilder {
+    host: Host,
+}
+
+impl ClientBuilder {
+    /// Adds a header that is sent with every request to the server, including the login
+    ///
+    /// The header is sent in addition to the default headers of the client.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.host.add_header(name, value);
+        self
+    }
+
+    /// Identifies the application in the User-Agent header of all requests
+    ///
+    /// The crate name and version are appended, e.g. `MyBackupTool/2.1 (immich-rs/0.2.0)`.
+    /// The User-Agent is independent of the `deviceId` of uploaded assets.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::builder("https://immich-web-url/api")
+    ///     .unwrap()
+    ///     .user_agent("MyBackupTool/2.1")
+    ///     .key("7F4BR7QLvGzyiqcIszQq1nZdvyqFY955yW9msrqyeD");
+    ///
+    /// assert!(client.is_ok());
+    /// ```
+    pub fn user_agent(mut self, name: &str) -> Self {
+        self.host
+            .add_header("User-Agent", &format!("{name} ({USER_AGENT})"));
+        self
+    }
+
+    /// Keeps email and password in the client to renew the session with [`Client::refresh`]
+    ///
+    /// The credentials are only used with [`ClientBuilder::email`]. They are kept in memory
+    /// for the lifetime of the client and are never shown in its `Debug` output.
+    pub fn keep_credentials(mut self) -> Self {
+        self.host.keep_credentials();
+        self
+    }
+
+    /// Connect to the Immich server with email and password authentication
+    pub fn email(self, email: &str, password: &str) -> ImmichResult<Client> {
+        self.host.email(email, password)
+    }
+
+    /// Connect to the Immich server with API key based authentication
+    pub fn key(self, key: &str) -> ImmichResult<Client> {
+        self.host.key(key)
+    }
+
+    /// Connect to the Immich server with an existing session access token
+    pub fn access_token(self, token: &str) -> ImmichResult<Client> {
+        self.host.access_token(token)
+    }
+}
+
+/// Returns the JSON of the test user, as sent by the server
+#[cfg(test)]
+pub(crate) fn user_json() -> String {
+    r#"{"id": "3fa85f64-5717-4562-b3fc-2c963f66afa6", "email": "a@b", "name": "A"}"#.to_string()
+}
+
+/// Returns the JSON of an unshared album of the test user, as sent by the server
+#[cfg(test)]
+pub(crate) fn album_json(id: &str, name: &str, asset_count: usize) -> String {
+    format!(
+        r#"{{
+            "albumName": "{name}",
+            "assetCount": {asset_count},
+            "id": "{id}",
+            "owner": {},
+            "shared": false
+        }}"#,
+        user_json()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::upload::Status;
+
+    #[test]
+    fn debug_hides_secrets() {
+        let client = Client::new(
+            Url::try_from("https://immich.example.com/api").unwrap(),
+            Authenticated::ApiKey("s3cr3t".to_string()),
+            vec![("CF-Access-Client-Secret".to_string(), "s3cr3t".to_string())],
+            ureq::agent(),
+        );
+        assert_eq!(
+            format!("{client:?}"),
+            "Client { url: \"https://immich.example.com/api\", auth: ApiKey(***) }"
+        );
+    }
+
+    #[test]
+    fn user_agent() {
+        let url = Url::try_from("https://immich.example.com/api").unwrap();
+        let client = Client::new(
+            url.clone(),
+            Authenticated::ApiKey("s3cr3t".to_string()),
+            Vec::new(),
+            ureq::agent(),
+        );
+        assert_eq!(client.get("/users").header("User-Agent"), Some(USER_AGENT));
+
+        let agent = format!("MyBackupTool/2.1 ({USER_AGENT})");
+        let client = Client::new(
+            url,
+            Authenticated::ApiKey("s3cr3t".to_string()),
+            vec![("User-Agent".to_string(), agent.clone())],
+            ureq::agent(),
+        );
+        let request = client.get("/users");
+        assert_eq!(request.header("User-Agent"), Some(agent.as_str()));
+        assert_eq!(request.all("User-Agent").len(), 1);
+    }
+
+    #[test]
+    fn cache_media_types() {
+        let requests = Arc::new(Mutex::new(0));
+        let counter = requests.clone();
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("GET", "/server/media-types") => {
+                *counter.lock().unwrap() += 1;
+                (200, r#"{"image": [".jpg"], "video": [".mp4"]}"#.to_string())
+            }
+            _ => (404, String::new()),
+        });
+
+        assert!(client
+            .supported_media_types()
+            .unwrap()
+            .supports("IMG_1.jpg"));
+        assert!(!client
+            .clone()
+            .supported_media_types()
+            .unwrap()
+            .supports("IMG_1.heic"));
+        assert_eq!(*requests.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn mock_responses() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("GET", "/users/me") => (
+                200,
+                r#"{"id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+                    "email": "email@somewhere", "name": "Username"}"#
+                    .to_string(),
+            ),
+            ("GET", "/users") => (403, r#"{"message": "Forbidden"}"#.to_string()),
+            ("POST", "/trash/empty") => (500, r#"{"message": "Trash is locked"}"#.to_string()),
+            ("POST", "/auth/validateToken") => (401, String::new()),
+            _ => (404, String::new()),
+        });
+
+        assert_eq!(client.user().unwrap().name(), "Username");
+        assert!(
+            matches!(client.users(), Err(ImmichError::Status(403, body)) if body.contains("Forbidden"))
+        );
+        assert!(matches!(client.albums(), Err(ImmichError::NotFound(_))));
+        assert!(
+            matches!(client.empty_trash(), Err(ImmichError::Status(500, body)) if body.contains("Trash is locked"))
+        );
+        assert!(matches!(client.check_connection(), Err(ImmichError::Auth)));
+    }
+
+    #[test]
+    fn upload_with_albums() {
+        let uploads = Arc::new(Mutex::new(0));
+        let counter = uploads.clone();
+        let client = Client::mock(move |method, path| match (method, path) {
+            ("POST", "/assets") => {
+                let mut counter = counter.lock().unwrap();
+                *counter += 1;
+                (
+                    201,
+                    format!(
+                        r#"{{"id": "f0edb589-1312-4161-b41e-0a18f127b3d{counter}", "status": "created"}}"#
+                    ),
+                )
+            }
+            ("GET", "/albums") => (
+                200,
+                format!(
+                    "[{}]",
+                    album_json("41a3a296-7e86-4eb4-8e44-aead03344fc9", "Holiday", 0)
+                ),
+            ),
+            ("PUT", "/albums/41a3a296-7e86-4eb4-8e44-aead03344fc9/assets") => (
+                200,
+                r#"[
+                    {"id": "f0edb589-1312-4161-b41e-0a18f127b3d1", "success": true},
+                    {"id": "f0edb589-1312-4161-b41e-0a18f127b3d2", "success": true}
+                ]"#
+                .to_string(),
+            ),
+            _ => (404, String::new()),
+        });
+        let assets = [
+            (
+                "IMG_1.jpg",
+                vec!["Holiday".to_string(), "Holiday".to_string()],
+            ),
+            (
+                "IMG_2.jpg",
+                vec!["Holiday".to_string(), "Wedding".to_string()],
+            ),
+            ("IMG_3.jpg", vec![]),
+        ]
+        .into_iter()
+        .map(|(name, albums)| {
+            let data = [b"\xFF\xD8\xFF", name.as_bytes()].concat();
+            (Asset::from_bytes(data, name.to_string()), albums)
+        });
+
+        // a single upload thread keeps the ids of the mock server in the order of the assets
+        let moved = client.upload_with_albums(1, assets, None).unwrap();
+
+        assert_eq!(*uploads.lock().unwrap(), 3);
+        assert_eq!(moved.len(), 4);
+        let without_album: Vec<&MovedAsset> = moved
+            .iter()
+            .filter(|moved| moved.album_name().is_none())
+            .collect();
+        assert_eq!(without_album.len(), 1);
+        assert!(without_album[0].success());
+        assert_eq!(without_album[0].upload_status(), Some(&Status::Created));
+        let holiday: Vec<&MovedAsset> = moved
+            .iter()
+            .filter(|moved| moved.album_name() == Some("Holiday"))
+            .collect();
+        assert_eq!(holiday.len(), 2);
+        assert!(holiday.iter().all(|moved| moved.success()));
+        assert!(holiday
+            .iter()
+            .all(|moved| moved.upload_status() == Some(&Status::Created)));
+        // the mock server can't create the "Wedding" album
+        let wedding: Vec<&MovedAsset> = moved
+            .iter()
+            .filter(|moved| moved.album_name() == Some("Wedding"))
+            .collect();
+        assert_eq!(wedding.len(), 1);
+        assert!(!wedding[0].success());
+    }
+
+    #[test]
+    fn refresh_session() {
+        let mut client = Client::mock(|method, path| match (method, path) {
+            ("POST", "/auth/login") => (201, r#"{"accessToken": "n3w"}"#.to_string()),
+            _ => (404, String::new()),
+        });
+        client.refresh().unwrap();
+        assert_eq!(client.auth().header(), ("x-api-key", "mock"));
+
+        client.auth = Authenticated::Cookie("immich_access_token=0ld".to_string());
+        let mut client = client.with_credentials(Credentials::new("email@somewhere", "s3cr3t"));
+        client.refresh().unwrap();
+        assert_eq!(
+            client.auth().header(),
+            ("Cookie", "immich_access_token=n3w")
+        );
+
+        let mut client = Client {
+            auth: Authenticated::Cookie("immich_access_token=0ld".to_string()),
+            ..Client::mock(|_, _| (401, r#"{"message": "Invalid token"}"#.to_string()))
+        };
+        assert!(matches!(client.refresh(), Err(ImmichError::Auth)));
+    }
+
+    #[test]
+    fn all_assets() {
+        let client = Client::mock(|method, path| {
+            let page = |id: &str, next: &str| {
+                format!(
+                    r#"{{"assets": {{"items": [{{
+                        "id": "{id}",
+                        "deviceAssetId": "IMG_1.jpg",
+                        "deviceId": "Immich-0.1 (Rust Client)",
+                        "fileCreatedAt": "2013-06-09T07:14:29.000Z",
+                        "fileModifiedAt": "2013-06-09T07:14:29.000Z",
+                        "type": "IMAGE"
+                    }}], "nextPage": {next}}}}}"#
+                )
+            };
+            match (method, path) {
+                ("POST", "/search/metadata") => {
+                    (200, page("f0edb589-1312-4161-b41e-0a18f127b3dd", r#""2""#))
+                }
+                _ => (404, String::new()),
+            }
+        });
+
+        let first = client.search(SearchQuery::new()).unwrap();
+        assert_eq!(first.items().len(), 1);
+        assert!(first.next().is_some());
+
+        // the mock can't tell pages apart and always returns a next page
+        let assets: Vec<_> = client.all_assets().take(3).collect();
+        assert_eq!(assets.len(), 3);
+        assert!(assets.iter().all(|asset| asset.is_ok()));
+
+        let client = Client::mock(|_, _| {
+            (
+                200,
+                r#"{"assets": {"items": [], "nextPage": null}}"#.to_string(),
+            )
+        });
+        assert_eq!(client.all_assets().count(), 0);
+    }
+
+    #[test]
+    fn user_by_id() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("GET", "/users/6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e") => (
+                200,
+                r#"{"id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+                    "email": "email@somewhere", "name": "Username"}"#
+                    .to_string(),
+            ),
+            _ => (500, String::new()),
+        });
+        let id = AssetId::try_from("6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e").unwrap();
+        assert_eq!(client.user_by_id(&id).unwrap().name(), "Username");
+        assert!(matches!(
+            client.user_by_id(&AssetId::default()),
+            Err(ImmichError::InvalidId)
+        ));
+    }
+
+    #[test]
+    fn check_connection() {
+        let client = Client::mock(|_, _| (200, String::new()));
+        assert!(client.check_connection().is_ok());
+
+        let client = Client::mock(|_, _| (401, r#"{"message": "Invalid API key"}"#.to_string()));
+        assert!(matches!(client.check_connection(), Err(ImmichError::Auth)));
+
+        let client = Client::mock(|_, _| (0, String::new()));
+        assert!(matches!(
+            client.check_connection(),
+            Err(ImmichError::Transport(..))
+        ));
+    }
+
+    #[test]
+    fn album_exists() {
+        let client = Client::mock(|method, path| match (method, path) {
+            ("GET", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd?withoutAssets=true") => (
+                200,
+                album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 0),
+            ),
+            ("GET", "/albums") => (
+                200,
+                format!(
+                    "[{}]",
+                    album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 0)
+                ),
+            ),
+            ("GET", "/albums/8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c?withoutAssets=true") => {
+                (400, r#"{"message": "Bad request"}"#.to_string())
+            }
+            ("GET", _) => (
+                400,
+                r#"{"message": "Not found or no album.read access"}"#.to_string(),
+            ),
+            _ => (500, String::new()),
+        });
+
+        let existing = AssetId::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap();
+        assert!(client.album_exists_by_id(&existing).unwrap());
+        let missing = AssetId::try_from("41a3a296-7e86-4eb4-8e44-aead03344fc9").unwrap();
+        assert!(!client.album_exists_by_id(&missing).unwrap());
+        let invalid = AssetId::try_from("8b4a7c1e-2f3d-4e5a-9b6c-7d8e9f0a1b2c").unwrap();
+        assert!(matches!(
+            client.album_exists_by_id(&invalid),
+            Err(ImmichError::Status(400, _))
+        ));
+
+        assert!(client.album_exists_by_name("Holiday").unwrap());
+        assert!(!client.album_exists_by_name("Wedding").unwrap());
+    }
+
+    #[test]
+    fn create_album_with() {
+        let client = Client::mock(|method, path| {
+            match (method, path) {
+            ("GET", "/users/me") => (200, user_json()),
+            ("POST", "/albums") => (
+                201,
+                album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday favorites", 0),
+            ),
+            ("PUT", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets") => (
+                200,
+                r#"[
+                    {"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": true},
+                    {"id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e", "success": false, "error": "not_found"}
+                ]"#
+                .to_string(),
+            ),
+            _ => (404, String::new()),
+        }
+        });
+        let ids = [
+            "41a3a296-7e86-4eb4-8e44-aead03344fc9",
+            "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+        ]
+        .into_iter()
+        .map(|id| AssetId::try_from(id).unwrap());
+
+        let album = client
+            .create_album_with("Holiday favorites".to_string(), ids)
+            .unwrap();
+        assert_eq!(album.name(), "Holiday favorites");
+        assert_eq!(album.len(), 1);
     }
 }
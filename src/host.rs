@@ -1,12 +1,15 @@
 use crate::client::ImmichClient;
-use ureq::json;
+use log::debug;
 
-use crate::auth::{Authenticated, Login};
+use crate::auth::{Authenticated, Credentials};
+use crate::utils::agent;
 use crate::{url::Url, Client};
 use crate::{ImmichError, ImmichResult};
 
 pub(crate) struct Host {
     url: Url,
+    headers: Vec<(String, String)>,
+    keep_credentials: bool,
 }
 
 impl Host {
@@ -16,34 +19,52 @@ impl Host {
     {
         Ok(Self {
             url: url.try_into()?,
+            headers: Vec::new(),
+            keep_credentials: false,
         })
     }
 
+    pub fn add_header(&mut self, name: &str, value: &str) {
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn keep_credentials(&mut self) {
+        self.keep_credentials = true;
+    }
+
     pub fn email(self, username: &str, password: &str) -> ImmichResult<Client> {
-        let response = ureq::post(&self.url.add_path("/auth/login"))
+        let credentials = Credentials::new(username, password);
+        let agent = agent()?;
+        let request = agent
+            .post(&self.url.add_path("/auth/login"))
             .add_default_header()
-            .send_json(json!({
-                "email": username,
-                "password": password,
-            }))?;
-
-        #[allow(clippy::to_string_in_format_args)]
-        if response.status() == 201 {
-            let login: Login = response.into_json()?;
-            let auth = Authenticated::Cookie(format!("immich_access_token={}", login.accessToken));
-            Ok(Client::new(self.url, auth))
+            .add_headers(&self.headers);
+        let auth = credentials.login(request)?;
+        let client = Client::new(self.url, auth, self.headers, agent);
+        if self.keep_credentials {
+            Ok(client.with_credentials(credentials))
         } else {
-            Err(ImmichError::Auth)
+            Ok(client)
         }
     }
 
+    pub fn access_token(self, token: &str) -> ImmichResult<Client> {
+        let auth = Authenticated::Cookie(format!("immich_access_token={token}"));
+        let client = Client::new(self.url, auth, self.headers, agent()?);
+        client.check_connection().map_err(|err| {
+            debug!("Access token could not be validated: {err}");
+            err
+        })?;
+        Ok(client)
+    }
+
     pub fn key(self, key: &str) -> ImmichResult<Client> {
         let auth = Authenticated::ApiKey(key.to_string());
-        let client = Client::new(self.url, auth);
-        if client.check_auth() {
-            Ok(client)
-        } else {
-            Err(ImmichError::Auth)
-        }
+        let client = Client::new(self.url, auth, self.headers, agent()?);
+        client.check_connection().map_err(|err| {
+            debug!("API key could not be validated: {err}");
+            err
+        })?;
+        Ok(client)
     }
 }
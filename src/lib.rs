@@ -7,14 +7,22 @@ mod auth;
 mod client;
 mod host;
 mod multipart;
+mod search;
+mod shared_link;
+mod stack;
 mod url;
 mod utils;
 
 pub mod takeout;
 
-pub use album::{Album, Albums};
+pub use album::{Album, AlbumDeletion, AlbumOrder, Albums, GetOrCreate};
 pub use api::requests::{AssetMoveError, MovedAsset};
 pub use api::upload;
-pub use asset::{Asset, AssetId, AssetRemoteStatus, AssetType};
-pub use client::Client;
-pub use utils::{DateTime, ImmichError, ImmichResult, User};
+pub use asset::{
+    Asset, AssetBuilder, AssetId, AssetRemoteStatus, AssetType, RemoteAsset, ThumbnailSize,
+};
+pub use client::{Client, ClientBuilder};
+pub use search::{Cursor, Page, SearchQuery};
+pub use shared_link::SharedLink;
+pub use stack::Stack;
+pub use utils::{DateTime, ImmichError, ImmichResult, MediaTypes, Statistics, User};
@@ -1,6 +1,8 @@
 use std::io;
 /// adapted from https://crates.io/crates/ureq_multipart
-use std::io::Write;
+use std::io::{Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug)]
 pub struct MultipartBuilder {
@@ -17,16 +19,38 @@ impl Default for MultipartBuilder {
 impl MultipartBuilder {
     pub fn new() -> Self {
         Self {
-            boundary: "IMMICHCLIENTMULTIPARTUPLOADBOUND".to_string(),
+            boundary: Self::boundary(),
             inner: Vec::new(),
             data_written: false,
         }
     }
+
+    /// Generates a unique boundary from the current time and a process-wide counter
+    fn boundary() -> String {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or_default();
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        format!(
+            "IMMICHCLIENTMULTIPARTUPLOADBOUND{:016x}{:016x}",
+            nanos,
+            count ^ u64::from(std::process::id()).rotate_left(32)
+        )
+    }
+
+    fn contains_boundary(&self, bytes: &[u8]) -> bool {
+        bytes
+            .windows(self.boundary.len())
+            .any(|window| window == self.boundary.as_bytes())
+    }
     /// add text field
     ///
     /// * name field name
     /// * text field text value
     pub fn add_text(mut self, name: &str, text: &str) -> io::Result<Self> {
+        debug_assert!(!self.contains_boundary(text.as_bytes()));
         self.write_field_headers(name, None, None)?;
         self.inner.write_all(text.as_bytes())?;
         Ok(self)
@@ -40,6 +64,7 @@ impl MultipartBuilder {
     ) -> io::Result<Self> {
         // This is necessary to make sure it is interpreted as a file on the server end.
         // let content_type = Some("application/octet-stream");
+        debug_assert!(!self.contains_boundary(bytes));
         self.write_field_headers(name, filename, None)?;
         self.inner.write_all(bytes)?;
         Ok(self)
@@ -88,9 +113,69 @@ impl MultipartBuilder {
 
         // always write the closing boundary, even for empty bodies
         write!(self.inner, "--{}--\r\n", self.boundary)?;
-        Ok((
-            format!("multipart/form-data; boundary={}", self.boundary),
-            self.inner,
-        ))
+        Ok((self.content_type(), self.inner))
+    }
+
+    /// multipart data with a final file field that is streamed from `reader`
+    ///
+    /// The file data is not buffered, `len` must be the number of bytes of `reader`.
+    ///
+    /// # Return
+    /// * (content_type,content_length,body)
+    ///    * content_type http header content type
+    ///    * content_length http header content length
+    ///    * body ureq.req.send(body)
+    pub fn finish_with_reader<R: Read>(
+        mut self,
+        reader: R,
+        len: u64,
+        name: &str,
+        filename: Option<&str>,
+    ) -> io::Result<(String, u64, impl Read)> {
+        self.write_field_headers(name, filename, None)?;
+        let tail = format!("\r\n--{}--\r\n", self.boundary).into_bytes();
+        let content_type = self.content_type();
+        let content_length = self.inner.len() as u64 + len + tail.len() as u64;
+        let body = io::Cursor::new(self.inner)
+            .chain(reader.take(len))
+            .chain(io::Cursor::new(tail));
+        Ok((content_type, content_length, body))
+    }
+
+    fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unique_boundaries() {
+        let first = MultipartBuilder::new();
+        let second = MultipartBuilder::new();
+        assert_ne!(first.boundary, second.boundary);
+    }
+
+    #[test]
+    fn payload_does_not_contain_boundary() {
+        let data = std::fs::read("./utils/garden.jpg").unwrap();
+        let builder = MultipartBuilder::new();
+        let boundary = builder.boundary.clone();
+        assert!(!builder.contains_boundary(&data));
+
+        let (content_type, body) = builder
+            .add_text("deviceAssetId", "garden.jpg")
+            .unwrap()
+            .add_bytes(&data, "assetData", Some("garden.jpg"))
+            .unwrap()
+            .finish()
+            .unwrap();
+        assert_eq!(
+            content_type,
+            format!("multipart/form-data; boundary={boundary}")
+        );
+        assert!(body.ends_with(format!("--{boundary}--\r\n").as_bytes()));
     }
 }
@@ -0,0 +1,208 @@
+use serde::Serialize;
+
+use crate::api::requests::SearchAssets;
+use crate::utils::Id;
+use crate::{Asset, AssetType, DateTime, ImmichError};
+
+/// Position of the next [`Page`] of a paginated server response
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cursor(usize);
+
+/// One page of a paginated server response
+///
+/// # Examples
+///
+/// ```no_run
+/// use immich::{Client, SearchQuery};
+///
+/// let client = Client::with_email(
+///     "https://immich-web-url/api",
+///     "email@somewhere",
+///     "s3cr3tpassword"
+/// ).unwrap();
+///
+/// let mut query = SearchQuery::new().favorite(true);
+/// loop {
+///     let page = client.search(query.clone()).unwrap();
+///     for asset in page.items() {
+///         println!("{}", asset.id());
+///     }
+///     match page.next() {
+///         Some(cursor) => query = query.cursor(cursor.clone()),
+///         None => break,
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Page<T> {
+    items: Vec<T>,
+    next: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+    /// The items of the page
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    /// Returns the items of the page
+    pub fn into_items(self) -> Vec<T> {
+        self.items
+    }
+
+    /// The cursor to fetch the next page, `None` if this is the last page
+    pub fn next(&self) -> Option<&Cursor> {
+        self.next.as_ref()
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<T>, Option<Cursor>) {
+        (self.items, self.next)
+    }
+}
+
+impl TryFrom<SearchAssets> for Page<Asset> {
+    type Error = ImmichError;
+
+    fn try_from(assets: SearchAssets) -> Result<Self, Self::Error> {
+        let next = match assets.nextPage {
+            Some(page) => Some(Cursor(
+                page.parse().map_err(|_| ImmichError::InvalidResponse)?,
+            )),
+            None => None,
+        };
+        Ok(Self {
+            items: assets.items,
+            next,
+        })
+    }
+}
+
+/// Filter for [`crate::Client::search`]
+///
+/// All filters are optional and combined, an empty query matches all assets.
+///
+/// # Examples
+///
+/// ```no_run
+/// use immich::{AssetType, Client, DateTime, SearchQuery};
+///
+/// let client = Client::with_email(
+///     "https://immich-web-url/api",
+///     "email@somewhere",
+///     "s3cr3tpassword"
+/// ).unwrap();
+///
+/// let query = SearchQuery::new()
+///     .taken_after(DateTime::new(2024, 6, 1, 0, 0, 0).unwrap())
+///     .taken_before(DateTime::new(2024, 9, 1, 0, 0, 0).unwrap())
+///     .asset_type(AssetType::Video);
+///
+/// for asset in client.search(query).unwrap().items() {
+///     println!("{}", asset.id());
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SearchQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    takenAfter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    takenBefore: Option<String>,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    asset_type: Option<AssetType>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    albumIds: Vec<Id>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    isFavorite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    page: Option<usize>,
+}
+
+impl SearchQuery {
+    /// Creates a query that matches all assets
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only matches assets taken at or after `date`
+    pub fn taken_after(mut self, date: DateTime) -> Self {
+        self.takenAfter = Some(date.to_string());
+        self
+    }
+
+    /// Only matches assets taken at or before `date`
+    pub fn taken_before(mut self, date: DateTime) -> Self {
+        self.takenBefore = Some(date.to_string());
+        self
+    }
+
+    /// Only matches assets of the given type
+    pub fn asset_type(mut self, asset_type: AssetType) -> Self {
+        self.asset_type = Some(asset_type);
+        self
+    }
+
+    /// Only matches assets in the album with the given id
+    ///
+    /// Use multiple times to match assets that are in all of the albums.
+    pub fn album(mut self, id: &Id) -> Self {
+        self.albumIds.push(id.clone());
+        self
+    }
+
+    /// Only matches favorite assets, or only non-favorite assets for `false`
+    pub fn favorite(mut self, favorite: bool) -> Self {
+        self.isFavorite = Some(favorite);
+        self
+    }
+
+    /// Matches assets by their content, e.g. `"dog on the beach"`
+    ///
+    /// The text is matched with the smart search of Immich, which requires
+    /// machine learning to be enabled on the server.
+    pub fn text(mut self, query: &str) -> Self {
+        self.query = Some(query.to_string());
+        self
+    }
+
+    /// Continues the search at the page of the `cursor`
+    pub fn cursor(mut self, cursor: Cursor) -> Self {
+        self.page = Some(cursor.0);
+        self
+    }
+
+    /// The search endpoint that supports all filters of the query
+    pub(crate) fn path(&self) -> &'static str {
+        if self.query.is_some() {
+            "/search/smart"
+        } else {
+            "/search/metadata"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_query() {
+        let query = SearchQuery::new();
+        assert_eq!(serde_json::to_string(&query).unwrap(), "{}");
+        assert_eq!(query.path(), "/search/metadata");
+
+        let query = SearchQuery::new()
+            .taken_after(DateTime::new(2024, 6, 1, 0, 0, 0).unwrap())
+            .asset_type(AssetType::Video)
+            .favorite(true)
+            .text("dog")
+            .cursor(Cursor(2));
+        assert_eq!(
+            serde_json::to_string(&query).unwrap(),
+            r#"{"takenAfter":"2024-06-01T00:00:00.000Z","type":"VIDEO","isFavorite":true,"query":"dog","page":2}"#
+        );
+        assert_eq!(query.path(), "/search/smart");
+    }
+}
@@ -0,0 +1,112 @@
+use serde::Deserialize;
+
+use crate::utils::Id;
+use crate::{Client, DateTime, ImmichError, ImmichResult};
+
+#[derive(Clone, Deserialize)]
+struct LinkedAlbum {
+    id: Id,
+}
+
+/// Public link to share an [`crate::Album`] or assets with people without an account
+///
+/// # Examples
+///
+/// ```no_run
+/// use immich::{Album, Client};
+///
+/// let client = Client::with_email(
+///     "https://immich-web-url/api",
+///     "email@somewhere",
+///     "s3cr3tpassword"
+/// ).unwrap();
+///
+/// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+/// for link in album.shared_links(&client).unwrap() {
+///     println!("{}: {}", link.key(), link.description());
+/// }
+/// ```
+#[allow(non_snake_case)]
+#[derive(Clone, Deserialize)]
+pub struct SharedLink {
+    id: Id,
+    key: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    expiresAt: Option<DateTime>,
+    #[serde(default)]
+    allowUpload: bool,
+    #[serde(default)]
+    allowDownload: bool,
+    #[serde(default)]
+    album: Option<LinkedAlbum>,
+}
+
+impl SharedLink {
+    /// The id of the shared link on the Immich server
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// The key that is part of the public URL of the shared link
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// The description of the shared link, empty if it has none
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+
+    /// The date when the link expires, `None` if it never expires
+    pub fn expires_at(&self) -> Option<&DateTime> {
+        self.expiresAt.as_ref()
+    }
+
+    /// Returns true if people with the link can upload images and videos
+    pub fn allow_upload(&self) -> bool {
+        self.allowUpload
+    }
+
+    /// Returns true if people with the link can download the images and videos
+    pub fn allow_download(&self) -> bool {
+        self.allowDownload
+    }
+
+    /// The id of the shared album, `None` if the link shares individual assets
+    pub fn album_id(&self) -> Option<&Id> {
+        self.album.as_ref().map(|album| &album.id)
+    }
+
+    /// Deletes the shared link on the Immich server
+    ///
+    /// The link can't be used anymore afterwards. Links that were deleted already
+    /// are not reported as an error.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{Album, Client};
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let album: Album = Album::get_or_create(&client, "My album".to_string()).unwrap().into();
+    /// for link in album.shared_links(&client).unwrap() {
+    ///     link.revoke(&client).unwrap();
+    /// }
+    /// ```
+    pub fn revoke(self, client: &Client) -> ImmichResult<()> {
+        if !self.id.is_safe() {
+            return Err(ImmichError::InvalidId);
+        }
+        match Client::read_empty(client.delete(&format!("/shared-links/{}", self.id)).call()) {
+            Err(ImmichError::NotFound(_)) => Ok(()),
+            result => result,
+        }
+    }
+}
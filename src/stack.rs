@@ -0,0 +1,38 @@
+use serde::Deserialize;
+
+use crate::utils::Id;
+use crate::AssetId;
+
+#[derive(Clone, Deserialize)]
+struct StackedAsset {
+    id: AssetId,
+}
+
+/// Group of images and videos that Immich shows as a single asset, e.g. burst photos
+///
+/// A stack is created with [`crate::Client::create_stack`]
+#[allow(non_snake_case)]
+#[derive(Clone, Deserialize)]
+pub struct Stack {
+    id: Id,
+    primaryAssetId: AssetId,
+    #[serde(default)]
+    assets: Vec<StackedAsset>,
+}
+
+impl Stack {
+    /// The id of the stack on the Immich server
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    /// The id of the asset that represents the stack, e.g. in the timeline
+    pub fn primary_asset_id(&self) -> &AssetId {
+        &self.primaryAssetId
+    }
+
+    /// The ids of all images and videos in the stack
+    pub fn asset_ids(&self) -> impl Iterator<Item = &AssetId> {
+        self.assets.iter().map(|asset| &asset.id)
+    }
+}
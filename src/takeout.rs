@@ -34,11 +34,23 @@
 //!     .unwrap();
 //!
 //! for asset in res {
-//!     println!("{}", asset.id())
+//!     println!("{}", asset.id().map(ToString::to_string).unwrap_or_default())
 //! }
 //! ```
 //!
+//! # Albums
+//!
+//! Every folder of the archive is recreated as an Immich album with the name of the folder,
+//! except for the special `Archive` and `Trash` folders. The album of an image or video is
+//! always the folder that directly contains it, also in nested folders.
+//!
+//! Albums that a partner or another account shared with you are exported to
+//! `Google Photos/Shared albums/<album>`. They are recreated as shared albums with the
+//! name prefixed by `Shared: `, so they are not merged with own albums of the same name
+//! (see [`TakeoutConfig::shared_album_prefix`]).
+//!
 
+mod config;
 mod file;
 mod media;
 mod metadata;
@@ -47,17 +59,24 @@ mod upload;
 use crate::takeout::media::Media;
 use crate::takeout::media::MediaStore;
 use flate2::read::GzDecoder;
-use std::collections::HashMap;
+use log::debug;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::io;
-use std::io::{Error, Read, Seek};
+use std::io::{Error, Read, Seek, SeekFrom};
+use std::iter::Flatten;
+use std::path::{Path, PathBuf};
+use std::vec::IntoIter;
 
 use tar::{Archive, Entries, Entry};
 use thiserror::Error;
 use time::OffsetDateTime;
 
 use crate::takeout::file::{FileType, Filename};
-pub use upload::Uploader;
+use crate::AssetType;
+pub use config::TakeoutConfig;
+pub use upload::{AlbumProgress, DuplicateAlbumPolicy, ImportSummary, Imported, Uploader};
 
 /// Error types used by the `takeout` submodule
 #[derive(Error, Debug)]
@@ -77,6 +96,18 @@ pub enum ParseError {
         #[from]
         source: Error,
     },
+    #[error("IO error reading {path}")]
+    IoAt { path: String, source: Error },
+}
+
+impl ParseError {
+    /// Adds the path of the archive entry to an IO error
+    fn io_at<P: AsRef<Path>>(path: P, source: Error) -> Self {
+        Self::IoAt {
+            path: path.as_ref().display().to_string(),
+            source,
+        }
+    }
 }
 
 type ParseResult<T> = Result<T, ParseError>;
@@ -104,10 +135,24 @@ impl HandleEdited {
     fn use_original(&self) -> bool {
         self != &HandleEdited::PreferEdited
     }
+
+    /// Returns true if both versions of the media are used
+    ///
+    /// In this case the edited version must use a different name than the original.
+    fn use_both(&self, media: &Media) -> bool {
+        self == &HandleEdited::UseBoth && media.edited() && media.original()
+    }
 }
 
 /// Handles the Takeout archive
 ///
+/// The archive is usually read from a [`File`], but any source that implements
+/// [`Read`] and [`Seek`] works, e.g. an in-memory [`Cursor`](std::io::Cursor).
+///
+/// The reader does not need to be wrapped in a [`BufReader`](std::io::BufReader), the
+/// gzip decoder already reads the archive in chunks of 32 KiB. Larger buffers do not
+/// speed up scanning noticeably, the time is spent decompressing the archive.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -123,13 +168,23 @@ impl HandleEdited {
 ///     println!("{}", record.name());
 /// }
 /// ```
-pub struct Takeout {
-    edited_files: HandleEdited,
+///
+/// ```no_run
+/// use std::io::Cursor;
+/// use immich::takeout::Takeout;
+///
+/// let data = std::fs::read("path/to/archive.tar.gz").unwrap();
+/// let archive = Takeout::new(Cursor::new(data)).unwrap();
+///
+/// println!("The archive contains {} images and videos", archive.len());
+/// ```
+pub struct Takeout<R: Read = File> {
+    config: TakeoutConfig,
     media: MediaStore,
-    archive: Archive<GzDecoder<File>>,
+    archives: Vec<Archive<GzDecoder<R>>>,
 }
 
-impl Takeout {
+impl<R: Read + Seek> Takeout<R> {
     /// Creates a new Takeout archive
     ///
     /// This method is blocking and will take some time to run.
@@ -152,8 +207,8 @@ impl Takeout {
     ///
     /// println!("The archive contains {} images and videos", archive.len());
     /// ```
-    pub fn new(file: File) -> ParseResult<Self> {
-        Self::with_rules(file, HandleEdited::PreferEdited)
+    pub fn new(reader: R) -> ParseResult<Self> {
+        Self::with_rules(reader, HandleEdited::PreferEdited)
     }
 
     /// Creates a new Takeout archive with defined rules for edited files
@@ -177,36 +232,162 @@ impl Takeout {
     ///
     /// println!("The archive contains {} images and videos", archive.len());
     /// ```
-    pub fn with_rules(mut file: File, edited_files: HandleEdited) -> ParseResult<Self> {
-        let f = file.try_clone()?;
-        let archive = Archive::new(GzDecoder::new(f));
-        let media = Self::first_scan(archive, &edited_files)?;
-        file.rewind()?;
+    pub fn with_rules(reader: R, edited_files: HandleEdited) -> ParseResult<Self> {
+        Self::from_parts(vec![reader], edited_files)
+    }
+
+    /// Creates a new Takeout archive with a custom configuration
+    ///
+    /// Use this method to change several rules at once, e.g. the handling of edited files
+    /// and additional file extensions (see [`TakeoutConfig`] for details).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::{HandleEdited, Takeout, TakeoutConfig};
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let config = TakeoutConfig::default().edited_files(HandleEdited::PreferOriginal);
+    /// let archive = Takeout::with_config(file, config).unwrap();
+    ///
+    /// println!("The archive contains {} images and videos", archive.len());
+    /// ```
+    pub fn with_config(reader: R, config: TakeoutConfig) -> ParseResult<Self> {
+        Self::scan(vec![reader], config, |_| {})
+    }
+
+    /// Creates a new Takeout archive that is split into multiple parts
+    ///
+    /// Google Takeout splits large exports into several archives, e.g. `takeout-001.tgz`,
+    /// `takeout-002.tgz`. The metadata of an image or video can be in a different part
+    /// than the image or video itself, and albums can span several parts. All parts are
+    /// scanned together and [`Takeout::records`] yields the records of each part in turn.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::{HandleEdited, Takeout};
+    ///
+    /// let parts = vec![
+    ///     File::open("path/to/takeout-001.tgz").unwrap(),
+    ///     File::open("path/to/takeout-002.tgz").unwrap(),
+    /// ];
+    /// let archive = Takeout::from_parts(parts, HandleEdited::PreferEdited).unwrap();
+    ///
+    /// println!("The archive contains {} images and videos", archive.len());
+    /// ```
+    pub fn from_parts(readers: Vec<R>, edited_files: HandleEdited) -> ParseResult<Self> {
+        Self::with_progress(readers, edited_files, |_| {})
+    }
+
+    /// Creates a new Takeout archive and reports the progress of the initial scan
+    ///
+    /// Works like [`Takeout::from_parts`], but calls `progress` after every entry of the
+    /// archive. Use this to give feedback while scanning very large archives.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::{HandleEdited, Takeout};
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let archive = Takeout::with_progress(vec![file], HandleEdited::PreferEdited, |progress| {
+    ///     print!(
+    ///         "\rScanning archive: {} files indexed ({}%)",
+    ///         progress.entries(),
+    ///         progress.bytes_read() * 100 / progress.total_bytes().max(1)
+    ///     );
+    /// })
+    /// .unwrap();
+    ///
+    /// println!("\nThe archive contains {} images and videos", archive.len());
+    /// ```
+    pub fn with_progress<F: FnMut(&ScanProgress)>(
+        readers: Vec<R>,
+        edited_files: HandleEdited,
+        progress: F,
+    ) -> ParseResult<Self> {
+        Self::scan(
+            readers,
+            TakeoutConfig::default().edited_files(edited_files),
+            progress,
+        )
+    }
+
+    /// Scans all parts of the archive and reports the progress after every entry
+    pub(crate) fn scan<F: FnMut(&ScanProgress)>(
+        mut readers: Vec<R>,
+        config: TakeoutConfig,
+        mut progress: F,
+    ) -> ParseResult<Self> {
+        let mut state = ScanProgress::default();
+        for reader in readers.iter_mut() {
+            state.total_bytes += reader.seek(SeekFrom::End(0))?;
+            reader.rewind()?;
+        }
+
+        let mut media = MediaStore::default();
+        let mut archives = Vec::with_capacity(readers.len());
+        for mut reader in readers {
+            let offset = state.bytes_read;
+            let bytes_read = Cell::new(0);
+            Self::first_scan(
+                &mut media,
+                Archive::new(GzDecoder::new(CountingReader::new(
+                    &mut reader,
+                    &bytes_read,
+                ))),
+                &config,
+                || {
+                    state.entries += 1;
+                    state.bytes_read = offset + bytes_read.get();
+                    progress(&state);
+                },
+            )?;
+            reader.rewind()?;
+            archives.push(Archive::new(GzDecoder::new(reader)));
+        }
         Ok(Self {
-            edited_files,
+            config,
             media,
-            archive: Archive::new(GzDecoder::new(file)),
+            archives,
         })
     }
 
-    fn first_scan(
-        mut archive: Archive<GzDecoder<File>>,
-        edited_files: &HandleEdited,
-    ) -> ParseResult<MediaStore> {
-        let mut media = MediaStore::default();
+    fn first_scan<S: Read, F: FnMut()>(
+        media: &mut MediaStore,
+        mut archive: Archive<GzDecoder<S>>,
+        config: &TakeoutConfig,
+        mut on_entry: F,
+    ) -> ParseResult<()> {
+        // Reading the next entry fails, if the data of the previous entry is truncated
+        let mut previous = PathBuf::new();
         for entry in archive.entries()? {
-            let mut entry = entry?;
+            let mut entry = entry.map_err(|err| ParseError::io_at(&previous, err))?;
+            previous = entry.path()?.to_path_buf();
+            on_entry();
 
-            let filename = Filename::try_from(&entry)?;
+            let filename = Filename::from_entry(&entry, config)?;
 
             match filename.filetype() {
-                FileType::Metadata => {
-                    if let Ok(date_taken) = metadata::parse(&mut entry) {
-                        media.add_metadata(&filename, date_taken);
+                FileType::Metadata if filename.is_album_metadata(config.extensions()) => {
+                    match metadata::parse_album(&mut entry) {
+                        Ok(true) => media.add_shared_album(&filename),
+                        Ok(false) => {}
+                        Err(err) => {
+                            debug!("Ignoring album metadata of {}: {err}", filename.album())
+                        }
                     }
                 }
+                FileType::Metadata => match metadata::parse(&mut entry) {
+                    Ok(details) => media.add_metadata(&filename, details),
+                    Err(err) => debug!("Ignoring metadata of {}: {err}", filename.name()),
+                },
                 FileType::Edited => {
-                    if edited_files.use_edited() {
+                    if config.handle_edited().use_edited() {
                         media.add_edited(&filename);
                     }
                 }
@@ -218,12 +399,19 @@ impl Takeout {
                 }
             }
         }
-        Ok(media)
+        Ok(())
     }
+}
 
+impl<R: Read> Takeout<R> {
     /// Returns the number of images and videos in the Google Takeout archive
     pub fn len(&self) -> usize {
         self.media.len()
+            + self
+                .media
+                .values()
+                .filter(|media| self.config.handle_edited().use_both(media))
+                .count()
     }
 
     /// returns `true` if the Google Takeout archive is empty and does not contain any
@@ -250,28 +438,245 @@ impl Takeout {
     ///     println!("{}", record.date_taken().unwrap());
     /// }
     /// ```
-    pub fn records(&mut self) -> ParseResult<Iter<'_>> {
-        let iter = self.archive.entries()?;
-        Ok(Iter::new(iter, &self.edited_files, &self.media))
+    pub fn records(&mut self) -> ParseResult<Iter<'_, R>> {
+        let parts = self
+            .archives
+            .iter_mut()
+            .map(|archive| archive.entries())
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Iter::new(Items::new(
+            parts,
+            &self.config,
+            &self.media,
+            false,
+        )))
+    }
+
+    /// Returns an iterator of [`Record`] that starts after the first `skip` records
+    ///
+    /// This resumes an interrupted import, e.g. after a crash: Persist the number of records
+    /// that were processed successfully and pass it as `skip` in the next run. The records are
+    /// counted the same way as by [`Takeout::records`], including records that yield an error.
+    ///
+    /// The `tar` library can only read the archive sequentially, so the skipped records are
+    /// still read and decompressed, but not processed. This is a lot faster than uploading
+    /// them again, but still takes some time for very large archives.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let mut archive = Takeout::new(file).unwrap();
+    ///
+    /// let done: usize = std::fs::read_to_string("progress.txt")
+    ///     .map(|progress| progress.trim().parse().unwrap())
+    ///     .unwrap_or(0);
+    ///
+    /// for (idx, record) in archive.records_from(done).unwrap().enumerate() {
+    ///     println!("{}", record.unwrap().name());
+    ///     std::fs::write("progress.txt", (done + idx + 1).to_string()).unwrap();
+    /// }
+    /// ```
+    pub fn records_from(&mut self, skip: usize) -> ParseResult<Iter<'_, R>> {
+        let mut records = self.records()?;
+        records.by_ref().take(skip).for_each(drop);
+        Ok(records)
+    }
+
+    /// Returns an iterator of all [`ArchiveItem`]s, including metadata and unknown files
+    ///
+    /// In contrast to [`Takeout::records`], this iterator also yields the JSON metadata files
+    /// and all other files of the archive. Images and videos follow the same rules for edited
+    /// files as [`Takeout::records`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::{ArchiveItem, Takeout};
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let mut archive = Takeout::new(file).unwrap();
+    ///
+    /// for item in archive.entries_all().unwrap() {
+    ///     match item.unwrap() {
+    ///         ArchiveItem::Media(record) => println!("Media: {}", record.name()),
+    ///         ArchiveItem::Metadata { name, json } => println!("Metadata of {name}: {json}"),
+    ///         ArchiveItem::Unknown { path } => println!("Unknown file: {path}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn entries_all(&mut self) -> ParseResult<Items<'_, R>> {
+        let parts = self
+            .archives
+            .iter_mut()
+            .map(|archive| archive.entries())
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Items::new(parts, &self.config, &self.media, true))
     }
 
+    /// Returns the names of all images and videos that have an edited version in the archive
+    ///
+    /// Edited versions are not tracked if the archive uses [`HandleEdited::PreferOriginal`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let archive = Takeout::new(file).unwrap();
+    ///
+    /// for name in archive.edited_assets() {
+    ///     println!("{name} was edited in Google Photos");
+    /// }
+    /// ```
+    pub fn edited_assets(&self) -> impl Iterator<Item = &str> {
+        self.media
+            .values()
+            .filter(|media| media.edited())
+            .map(|media| media.name())
+    }
+
+    /// Returns the names of all images and videos without a matching metadata JSON file
+    ///
+    /// The metadata is matched by the filename, which Google shortens and numbers in
+    /// many different ways. Assets of unmatched metadata files fall back to the default
+    /// timestamps and lose their description, location and favorite status.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let archive = Takeout::new(file).unwrap();
+    ///
+    /// for name in archive.assets_missing_metadata() {
+    ///     println!("{name} has no metadata, its date will be wrong");
+    /// }
+    /// ```
+    pub fn assets_missing_metadata(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .media
+            .values()
+            .filter(|media| (media.original() || media.edited()) && media.date_taken().is_none())
+            .map(|media| media.name())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Returns the names of the edited and the original version of all media that uses both
+    pub(crate) fn edited_pairs(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.media
+            .values()
+            .filter(|media| self.config.handle_edited().use_both(media))
+            .map(|media| (media.edited_name(), media.name()))
+    }
+
+    /// Changes the handling of edited files after the archive was scanned
+    ///
+    /// Archives scanned with [`HandleEdited::PreferOriginal`] don't track edited files,
+    /// they must not be switched to another handling.
+    pub(crate) fn set_edited_files(&mut self, edited_files: HandleEdited) {
+        self.config.set_handle_edited(edited_files);
+    }
+
+    pub(crate) fn config(&self) -> &TakeoutConfig {
+        &self.config
+    }
+
+    /// Returns the media of a [`Record::name`]
+    pub(crate) fn media(&self, name: &str) -> Option<&Media> {
+        self.media.get(&Filename::normalize(name))
+    }
+
+    /// Returns all albums with the names of their images and videos
+    ///
+    /// The images and videos of every album are sorted by the date they were taken, so that
+    /// they are added to the Immich album chronologically. Media without a date come last,
+    /// media with the same date are sorted by name.
     pub fn albums(&self) -> TakeoutAlbums {
         let mut albums = TakeoutAlbums::default();
+        let mut dated: HashMap<&str, Vec<(Option<OffsetDateTime>, &str)>> = HashMap::new();
         for file in self.media.values() {
             for album in file.albums() {
-                albums
-                    .entry(album)
-                    .and_modify(|album_list| album_list.push(file.name()))
-                    .or_insert(vec![file.name()]);
+                if self.media.shared(album) {
+                    albums.shared.insert(album);
+                }
+                let album_list = dated.entry(album).or_default();
+                album_list.push((file.date_taken(), file.name()));
+                if self.config.handle_edited().use_both(file) {
+                    album_list.push((file.date_taken(), file.edited_name()));
+                }
             }
         }
+        for (album, mut album_list) in dated {
+            album_list.sort_by_key(|&(date, name)| (date.is_none(), date, name));
+            albums.inner.insert(
+                album,
+                album_list.into_iter().map(|(_, name)| name).collect(),
+            );
+        }
         albums
     }
 }
 
+/// Progress of the initial scan of a Takeout archive, see [`Takeout::with_progress`]
+#[derive(Clone, Debug, Default)]
+pub struct ScanProgress {
+    entries: usize,
+    bytes_read: u64,
+    total_bytes: u64,
+}
+
+impl ScanProgress {
+    /// The number of files in the archive that were scanned so far
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    /// The number of compressed bytes that were read from the archive so far
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// The size of the compressed archive in bytes, including all parts
+    pub fn total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+}
+
+/// Counts the bytes read from the underlying reader
+struct CountingReader<'a, R> {
+    inner: R,
+    bytes_read: &'a Cell<u64>,
+}
+
+impl<'a, R: Read> CountingReader<'a, R> {
+    fn new(inner: R, bytes_read: &'a Cell<u64>) -> Self {
+        Self { inner, bytes_read }
+    }
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + len as u64);
+        Ok(len)
+    }
+}
+
 #[derive(Default)]
 pub struct TakeoutAlbums<'a> {
     inner: HashMap<&'a str, Vec<&'a str>>,
+    shared: HashSet<&'a str>,
 }
 
 impl<'a> TakeoutAlbums<'a> {
@@ -282,9 +687,87 @@ impl<'a> TakeoutAlbums<'a> {
         self.inner.entry(key)
     }
 
-    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, &str, std::vec::Vec<&str>> {
+    pub fn iter(&self) -> std::collections::hash_map::Iter<'_, &'a str, std::vec::Vec<&'a str>> {
         self.inner.iter()
     }
+
+    /// Returns the names of the images and videos in the album
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let archive = Takeout::new(file).unwrap();
+    ///
+    /// if let Some(names) = archive.albums().get("Holiday") {
+    ///     println!("Holiday: {}", names.join(", "));
+    /// }
+    /// ```
+    pub fn get(&self, album: &str) -> Option<&Vec<&'a str>> {
+        self.inner.get(album)
+    }
+
+    /// Returns true if the archive contains the album
+    pub fn contains_key(&self, album: &str) -> bool {
+        self.inner.contains_key(album)
+    }
+
+    /// The number of albums
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if there are no albums
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns true if the album was shared in Google Photos
+    ///
+    /// Google Takeout does not export who the album was shared with, only that it was
+    /// shared, e.g. via link.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let archive = Takeout::new(file).unwrap();
+    ///
+    /// let albums = archive.albums();
+    /// for (album, _) in albums.iter().filter(|(album, _)| albums.is_shared(album)) {
+    ///     println!("{album} was shared");
+    /// }
+    /// ```
+    pub fn is_shared(&self, album: &str) -> bool {
+        self.shared.contains(album)
+    }
+
+    /// The number of images and videos per album
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let archive = Takeout::new(file).unwrap();
+    ///
+    /// for (album, count) in archive.albums().counts() {
+    ///     println!("{album}: {count} photos");
+    /// }
+    /// ```
+    pub fn counts(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.inner
+            .iter()
+            .map(|(album, files)| (*album, files.len()))
+    }
 }
 
 impl<'a> IntoIterator for TakeoutAlbums<'a> {
@@ -295,6 +778,14 @@ impl<'a> IntoIterator for TakeoutAlbums<'a> {
     }
 }
 
+impl<'a, 'b> IntoIterator for &'b TakeoutAlbums<'a> {
+    type Item = (&'b &'a str, &'b Vec<&'a str>);
+    type IntoIter = std::collections::hash_map::Iter<'b, &'a str, std::vec::Vec<&'a str>>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.inner.iter()
+    }
+}
+
 /// Iterator of [`Record`]
 ///
 /// This iterator can be created from [`Takeout::records`]
@@ -332,73 +823,169 @@ impl<'a> IntoIterator for TakeoutAlbums<'a> {
 ///     File::create("./file1.jpg").unwrap().write_all(&record.unwrap().data()).unwrap();
 /// })
 /// ```
-pub struct Iter<'a> {
-    iter: Entries<'a, GzDecoder<File>>,
-    edited_files: &'a HandleEdited,
+pub struct Iter<'a, R: Read = File> {
+    items: Items<'a, R>,
+}
+
+impl<'a, R: Read> Iter<'a, R> {
+    fn new(items: Items<'a, R>) -> Self {
+        Self { items }
+    }
+}
+
+impl<'a, R: Read> Iterator for Iter<'a, R> {
+    type Item = ParseResult<Record<'a, R>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.items.next()? {
+                Ok(ArchiveItem::Media(record)) => return Some(Ok(record)),
+                Ok(_) => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+/// An entry of the Takeout archive, see [`Takeout::entries_all`]
+// Items are consumed right away, so boxing the `Record` is not worth an allocation
+#[allow(clippy::large_enum_variant)]
+pub enum ArchiveItem<'a, R: Read = File> {
+    /// An image or video, the same as yielded by [`Takeout::records`]
+    Media(Record<'a, R>),
+    /// A JSON metadata file
+    Metadata {
+        /// The name of the image or video that the metadata belongs to,
+        /// see [`Record::name`]
+        name: String,
+        /// The raw JSON content
+        json: String,
+    },
+    /// Any other file of the archive
+    Unknown {
+        /// The path of the file inside the archive
+        path: String,
+    },
+}
+
+/// Iterator of all [`ArchiveItem`]s of the Takeout archive
+///
+/// This iterator can be created from [`Takeout::entries_all`]. The same restrictions
+/// as for [`Iter`] apply: Each item should be consumed directly, before advancing the iterator.
+pub struct Items<'a, R: Read = File> {
+    iter: Flatten<IntoIter<Entries<'a, GzDecoder<R>>>>,
+    config: &'a TakeoutConfig,
     media: &'a MediaStore,
+    previous: PathBuf,
+    metadata: bool,
 }
 
-impl<'a> Iter<'a> {
+impl<'a, R: Read> Items<'a, R> {
     fn new(
-        iter: Entries<'a, GzDecoder<File>>,
-        edited_files: &'a HandleEdited,
+        parts: Vec<Entries<'a, GzDecoder<R>>>,
+        config: &'a TakeoutConfig,
         media: &'a MediaStore,
+        metadata: bool,
     ) -> Self {
         Self {
-            iter,
-            edited_files,
+            iter: parts.into_iter().flatten(),
+            config,
             media,
+            previous: PathBuf::new(),
+            metadata,
         }
     }
 
     fn edited_exists(&self, filename: &Filename) -> bool {
-        self.media
-            .get(filename.name())
-            .expect("Media must exist")
-            .edited()
-    }
-
-    fn record(&mut self, entry: Entry<'a, GzDecoder<File>>) -> <Self as Iterator>::Item {
-        Record::try_from((self.media, entry))
+        self.media.get(filename.name()).is_some_and(Media::edited)
     }
 
-    fn original(
-        &mut self,
+    /// Returns the record of the file, or `None` if the file was not tracked by the scan
+    fn record(
+        &self,
         filename: &Filename,
-        entry: Entry<'a, GzDecoder<File>>,
-    ) -> Option<<Self as Iterator>::Item> {
-        if !self.edited_exists(filename) || self.edited_files.use_original() {
-            Some(self.record(entry))
+        entry: Entry<'a, GzDecoder<R>>,
+    ) -> Option<ArchiveItem<'a, R>> {
+        let Some(media) = self.media.get(filename.name()) else {
+            debug!("Ignoring {}, it was not found in the scan", filename.name());
+            return None;
+        };
+        let name = if filename.filetype() == &FileType::Edited
+            && self.config.handle_edited().use_both(media)
+        {
+            media.edited_name()
         } else {
-            self.next()
-        }
+            media.name()
+        };
+        Some(ArchiveItem::Media(Record::new(
+            media,
+            name,
+            entry,
+            self.config,
+        )))
     }
 
-    fn edited(&mut self, entry: Entry<'a, GzDecoder<File>>) -> Option<<Self as Iterator>::Item> {
-        if self.edited_files.use_edited() {
-            Some(self.record(entry))
-        } else {
-            self.next()
-        }
+    fn metadata(
+        &self,
+        filename: &Filename,
+        mut entry: Entry<'a, GzDecoder<R>>,
+    ) -> ParseResult<ArchiveItem<'a, R>> {
+        let mut json = String::new();
+        let _ = entry
+            .read_to_string(&mut json)
+            .map_err(|err| ParseError::io_at(&self.previous, err))?;
+        Ok(ArchiveItem::Metadata {
+            name: filename.name().to_string(),
+            json,
+        })
     }
 }
 
-impl<'a> Iterator for Iter<'a> {
-    type Item = ParseResult<Record<'a>>;
+impl<'a, R: Read> Iterator for Items<'a, R> {
+    type Item = ParseResult<ArchiveItem<'a, R>>;
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(entry) = self.iter.next() {
-            let entry = entry.unwrap();
+        loop {
+            let entry = match self.iter.next()? {
+                Ok(entry) => entry,
+                Err(err) => return Some(Err(ParseError::io_at(&self.previous, err))),
+            };
 
-            let filename = Filename::try_from(&entry).unwrap();
+            let filename = match Filename::from_entry(&entry, self.config) {
+                Ok(filename) => filename,
+                Err(err) => return Some(Err(err)),
+            };
+            if let Ok(path) = entry.path() {
+                self.previous = path.to_path_buf();
+            }
 
             match filename.filetype() {
-                FileType::Metadata => self.next(),
-                FileType::Original => self.original(&filename, entry),
-                FileType::Edited => self.edited(entry),
-                _ => self.next(),
+                FileType::Metadata => {
+                    if self.metadata {
+                        return Some(self.metadata(&filename, entry));
+                    }
+                }
+                FileType::Original => {
+                    if !self.edited_exists(&filename) || self.config.handle_edited().use_original()
+                    {
+                        if let Some(record) = self.record(&filename, entry) {
+                            return Some(Ok(record));
+                        }
+                    }
+                }
+                FileType::Edited => {
+                    if self.config.handle_edited().use_edited() {
+                        if let Some(record) = self.record(&filename, entry) {
+                            return Some(Ok(record));
+                        }
+                    }
+                }
+                FileType::Unknown => {
+                    if self.metadata {
+                        return Some(Ok(ArchiveItem::Unknown {
+                            path: self.previous.display().to_string(),
+                        }));
+                    }
+                }
             }
-        } else {
-            None
         }
     }
 }
@@ -407,14 +994,36 @@ impl<'a> Iterator for Iter<'a> {
 ///
 /// The reference is a lazy reference and will only read the actual
 /// file contents when needed.
-pub struct Record<'a> {
+pub struct Record<'a, R: Read = File> {
     media: &'a Media,
-    entry: Entry<'a, GzDecoder<File>>,
+    name: &'a str,
+    entry: Entry<'a, GzDecoder<R>>,
+    asset_type: AssetType,
+    /// The file contents, or the error that occurred while reading them
+    buffer: Option<io::Result<io::Cursor<Vec<u8>>>>,
 }
 
-impl<'a> Record<'a> {
-    fn new(media: &'a Media, entry: Entry<'a, GzDecoder<File>>) -> Self {
-        Self { media, entry }
+impl<'a, R: Read> Record<'a, R> {
+    /// Motion photos keep the video at the end of the image file
+    const MOTION_TAIL: usize = 64 * 1024;
+
+    fn new(
+        media: &'a Media,
+        name: &'a str,
+        entry: Entry<'a, GzDecoder<R>>,
+        config: &TakeoutConfig,
+    ) -> Self {
+        let asset_type = entry
+            .path()
+            .map(|path| config.asset_type(&path))
+            .unwrap_or_default();
+        Self {
+            media,
+            name,
+            entry,
+            asset_type,
+            buffer: None,
+        }
     }
 
     /// Date and time when the photo or video was taken
@@ -442,9 +1051,53 @@ impl<'a> Record<'a> {
         }
     }
 
+    /// The description of the image/video in Google Photos, if any
+    pub fn description(&self) -> Option<&str> {
+        self.media.description()
+    }
+
+    /// Returns true if the image/video is a favorite in Google Photos
+    pub fn favorite(&self) -> bool {
+        self.media.favorite()
+    }
+
+    /// Latitude and longitude of the image/video, if known to Google Photos
+    pub fn location(&self) -> Option<(f64, f64)> {
+        self.media.location()
+    }
+
+    /// Names of the people that Google Photos recognized in the image/video
+    ///
+    /// Immich runs its own face detection, so the names are not uploaded. Use them to
+    /// name the people in Immich after the import. Empty if the metadata does not
+    /// contain any people.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let mut archive = Takeout::new(file).unwrap();
+    ///
+    /// for record in archive.records().unwrap() {
+    ///     let record = record.unwrap();
+    ///     if !record.people().is_empty() {
+    ///         println!("{}: {}", record.name(), record.people().join(", "));
+    ///     }
+    /// }
+    /// ```
+    pub fn people(&self) -> &[String] {
+        self.media.people()
+    }
+
     /// File name
+    ///
+    /// If both the edited and the original version are used (see [`HandleEdited::UseBoth`]),
+    /// the edited version keeps its `-edited` suffix, so that both records have distinct names.
     pub fn name(&self) -> &str {
-        self.media.name()
+        self.name
     }
 
     /// Returns true if the image/video is not flagged as edited in the archive
@@ -458,10 +1111,90 @@ impl<'a> Record<'a> {
     }
 
     /// List of albums that the image/video is in
+    ///
+    /// The special `Archive` and `Trash` folders are not albums, see
+    /// [`Record::archived`] and [`Record::trashed`].
     pub fn albums(&self) -> &[String] {
         self.media.albums()
     }
 
+    /// Returns true if the image/video was archived in Google Photos
+    pub fn archived(&self) -> bool {
+        self.media.archived()
+    }
+
+    /// Returns true if the image/video was in the trash of Google Photos
+    pub fn trashed(&self) -> bool {
+        self.media.trashed()
+    }
+
+    /// The [`AssetType`] of the record, derived from the file extension
+    ///
+    /// Additional extensions are mapped as configured with [`TakeoutConfig::extension`].
+    pub fn asset_type(&self) -> AssetType {
+        self.asset_type.clone()
+    }
+
+    /// Returns true if the image is a motion photo with an embedded video
+    ///
+    /// Some cameras store motion photos as a single image file with the video clip appended
+    /// to the image data. This method looks for the trailing MP4 `moov` atom or the
+    /// `MotionPhoto_Data` marker in the last bytes of the image.
+    ///
+    /// The check reads the file contents into memory. They remain available via
+    /// [`Record::data`] and the `Read` trait. Videos and unreadable files are never
+    /// reported as motion photos.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::fs::File;
+    /// use immich::takeout::Takeout;
+    ///
+    /// let file = File::open("path/to/archive.tar.gz").unwrap();
+    /// let mut archive = Takeout::new(file).unwrap();
+    ///
+    /// for record in archive.records().unwrap() {
+    ///     let mut record = record.unwrap();
+    ///     if record.has_embedded_motion() {
+    ///         println!("{} is a motion photo", record.name());
+    ///     }
+    /// }
+    /// ```
+    pub fn has_embedded_motion(&mut self) -> bool {
+        if self.asset_type() != AssetType::Image {
+            return false;
+        }
+        let Ok(data) = self.buffered() else {
+            return false;
+        };
+        let tail = &data[data.len().saturating_sub(Self::MOTION_TAIL)..];
+        [b"moov".as_slice(), b"MotionPhoto_Data".as_slice()]
+            .iter()
+            .any(|marker| tail.windows(marker.len()).any(|window| window == *marker))
+    }
+
+    /// Reads the file contents into the internal buffer, if not done already
+    ///
+    /// A read error is kept and returned by all further reads, so that a partially read
+    /// file is never used as the file contents.
+    fn buffered(&mut self) -> io::Result<&[u8]> {
+        let Self { entry, buffer, .. } = self;
+        match buffer.get_or_insert_with(|| {
+            let mut bytes = Vec::with_capacity(entry.size().try_into().unwrap_or_default());
+            entry
+                .read_to_end(&mut bytes)
+                .map(|_| io::Cursor::new(bytes))
+        }) {
+            Ok(buffer) => Ok(buffer.get_ref().as_slice()),
+            Err(err) => Err(Self::buffer_error(err)),
+        }
+    }
+
+    fn buffer_error(err: &io::Error) -> io::Error {
+        io::Error::new(err.kind(), err.to_string())
+    }
+
     /// Actual file contents
     ///
     /// This method uses a blocking reader to read the data from the tar archive.
@@ -469,27 +1202,1150 @@ impl<'a> Record<'a> {
     pub fn data(&mut self) -> Vec<u8> {
         let mut bytes = Vec::with_capacity(self.entry.size().try_into().unwrap());
 
-        self.entry.read_to_end(&mut bytes).unwrap();
+        self.read_to_end(&mut bytes).unwrap();
         bytes
     }
 }
 
-impl Read for Record<'_> {
+impl<R: Read> Read for Record<'_, R> {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.entry.read(buf)
+        match &mut self.buffer {
+            Some(Ok(buffer)) => buffer.read(buf),
+            Some(Err(err)) => Err(Self::buffer_error(err)),
+            None => self.entry.read(buf),
+        }
     }
 
     fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
-        self.entry.read_to_end(buf)
+        match &mut self.buffer {
+            Some(Ok(buffer)) => buffer.read_to_end(buf),
+            Some(Err(err)) => Err(Self::buffer_error(err)),
+            None => self.entry.read_to_end(buf),
+        }
     }
 }
 
-impl<'a> TryFrom<(&'a MediaStore, Entry<'a, GzDecoder<File>>)> for Record<'a> {
-    type Error = ParseError;
-    fn try_from(value: (&'a MediaStore, Entry<'a, GzDecoder<File>>)) -> Result<Self, Self::Error> {
-        let entry = value.1;
-        let filename = Filename::try_from(&entry)?;
-        let media = value.0.get(filename.name()).expect("Media must exist");
-        Ok(Self::new(media, entry))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{album_json, user_json};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    /// Creates a gzipped tar archive with the given files in memory
+    fn archive_bytes(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (filename, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, filename, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    /// Temporary file of a Takeout archive, it is deleted when dropped
+    struct TempArchive(PathBuf);
+
+    impl AsRef<Path> for TempArchive {
+        fn as_ref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempArchive {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    /// Writes a gzipped tar archive with the given files to a temporary file
+    fn archive(name: &str, files: &[(&str, &[u8])]) -> TempArchive {
+        let path = std::env::temp_dir().join(format!("immich-{}-{name}.tgz", std::process::id()));
+        std::fs::write(&path, archive_bytes(files)).unwrap();
+        TempArchive(path)
+    }
+
+    /// Opens a temporary archive with the given files, the file is deleted right away
+    fn archive_file(name: &str, files: &[(&str, &[u8])]) -> File {
+        File::open(archive(name, files)).unwrap()
+    }
+
+    fn names(takeout: &mut Takeout) -> Vec<String> {
+        let mut names: Vec<String> = takeout
+            .records()
+            .unwrap()
+            .map(|record| record.unwrap().name().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    const FILES: [(&str, &[u8]); 3] = [
+        ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"original"),
+        ("Takeout/Google Photos/Holiday/IMG_1-edited.jpg", b"edited"),
+        ("Takeout/Google Photos/Holiday/IMG_2.jpg", b"other"),
+    ];
+
+    #[test]
+    fn use_both_edited_and_original() {
+        let file = archive_file("use_both", &FILES);
+        let mut takeout = Takeout::with_rules(file, HandleEdited::UseBoth).unwrap();
+
+        assert_eq!(takeout.len(), 3);
+        assert_eq!(
+            names(&mut takeout),
+            vec!["IMG_1-edited.jpg", "IMG_1.jpg", "IMG_2.jpg"]
+        );
+
+        let albums = takeout.albums();
+        assert_eq!(albums.len(), 1);
+        assert_eq!(albums.counts().collect::<Vec<_>>(), vec![("Holiday", 3)]);
+        let (_, album) = albums.iter().next().unwrap();
+        let mut album = album.clone();
+        album.sort();
+        assert_eq!(album, vec!["IMG_1-edited.jpg", "IMG_1.jpg", "IMG_2.jpg"]);
+    }
+
+    #[test]
+    fn prefer_edited() {
+        let file = archive_file("prefer_edited", &FILES);
+        let mut takeout = Takeout::with_rules(file, HandleEdited::PreferEdited).unwrap();
+
+        assert_eq!(takeout.len(), 2);
+        let mut data = Vec::new();
+        for record in takeout.records().unwrap() {
+            let mut record = record.unwrap();
+            if record.name() == "IMG_1.jpg" {
+                data = record.data();
+            }
+        }
+        assert_eq!(data, b"edited");
+    }
+
+    #[test]
+    fn duplicate_numbered_metadata() {
+        let file = archive_file(
+            "duplicate_numbered",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1234.jpg.json",
+                    br#"{"photoTakenTime": {"timestamp": "1370762069"}}"#,
+                ),
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1234.jpg(1).json",
+                    br#"{"photoTakenTime": {"timestamp": "1400220491"}}"#,
+                ),
+                ("Takeout/Google Photos/Holiday/IMG_1234.jpg", b"first"),
+                ("Takeout/Google Photos/Holiday/IMG_1234(1).jpg", b"second"),
+            ],
+        );
+        let mut takeout = Takeout::new(file).unwrap();
+        assert_eq!(takeout.len(), 2);
+
+        for record in takeout.records().unwrap() {
+            let record = record.unwrap();
+            let timestamp = record.date_taken().unwrap().unix_timestamp();
+            match record.name() {
+                "IMG_1234.jpg" => assert_eq!(timestamp, 1370762069),
+                "IMG_1234(1).jpg" => assert_eq!(timestamp, 1400220491),
+                other => panic!("Unexpected record {other}"),
+            }
+        }
+    }
+
+    #[test]
+    fn asset_timestamps_from_metadata() {
+        let file = archive_file(
+            "asset_timestamps",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg.json",
+                    br#"{"photoTakenTime": {"timestamp": "1370762069"}}"#,
+                ),
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"with metadata"),
+                (
+                    "Takeout/Google Photos/Holiday/IMG_2.jpg",
+                    b"without metadata",
+                ),
+            ],
+        );
+        let mut takeout = Takeout::new(file).unwrap();
+        assert_eq!(takeout.assets_missing_metadata(), vec!["IMG_2.jpg"]);
+
+        for record in takeout.records().unwrap() {
+            let asset = crate::Asset::try_from(record.unwrap()).unwrap();
+            match asset.device_asset_id() {
+                "IMG_1.jpg" => {
+                    assert_eq!(asset.created_at().to_string(), "2013-06-09T07:14:29.000Z");
+                    assert_eq!(asset.modified_at().to_string(), "2013-06-09T07:14:29.000Z");
+                }
+                "IMG_2.jpg" => {
+                    let default = crate::DateTime::default().to_string();
+                    assert_eq!(asset.created_at().to_string(), default);
+                    assert_eq!(asset.modified_at().to_string(), default);
+                }
+                other => panic!("Unexpected asset {other}"),
+            }
+        }
+    }
+
+    #[test]
+    fn albums_sorted_by_date() {
+        let file = archive_file(
+            "albums_sorted_by_date",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg.json",
+                    br#"{"photoTakenTime": {"timestamp": "1370762069"}}"#,
+                ),
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"late"),
+                (
+                    "Takeout/Google Photos/Holiday/IMG_2.jpg.json",
+                    br#"{"photoTakenTime": {"timestamp": "1270762069"}}"#,
+                ),
+                ("Takeout/Google Photos/Holiday/IMG_2.jpg", b"early"),
+                ("Takeout/Google Photos/Holiday/IMG_0.jpg", b"no date"),
+            ],
+        );
+        let takeout = Takeout::new(file).unwrap();
+        assert_eq!(
+            takeout.albums().get("Holiday").unwrap(),
+            &vec!["IMG_2.jpg", "IMG_1.jpg", "IMG_0.jpg"]
+        );
+    }
+
+    #[test]
+    fn prefer_original() {
+        let file = archive_file("prefer_original", &FILES);
+        let mut takeout = Takeout::with_rules(file, HandleEdited::PreferOriginal).unwrap();
+
+        assert_eq!(takeout.len(), 2);
+        assert_eq!(names(&mut takeout), vec!["IMG_1.jpg", "IMG_2.jpg"]);
+    }
+
+    #[test]
+    fn keep_originals_prefer_original() {
+        let config = || TakeoutConfig::default().edited_files(HandleEdited::PreferOriginal);
+
+        let path = archive("keep_originals_prefer_original", &FILES);
+        let mut uploader = Uploader::with_config(&path, config()).unwrap();
+        uploader.keep_originals();
+        assert_eq!(uploader.len(), 2);
+        let mut names: Vec<String> = uploader
+            .assets()
+            .unwrap()
+            .map(|asset| asset.filename().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["IMG_1.jpg", "IMG_2.jpg"]);
+
+        let path = archive(
+            "keep_originals_edited_only",
+            &[("Takeout/Google Photos/Holiday/IMG_1-edited.jpg", b"edited")],
+        );
+        let mut uploader = Uploader::with_config(&path, config()).unwrap();
+        uploader.keep_originals();
+        assert_eq!(uploader.assets().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn edited_assets() {
+        let file = archive_file("edited_assets", &FILES);
+        let takeout = Takeout::new(file).unwrap();
+        assert_eq!(
+            takeout.edited_assets().collect::<Vec<_>>(),
+            vec!["IMG_1.jpg"]
+        );
+
+        let file = archive_file("edited_assets_original", &FILES);
+        let takeout = Takeout::with_rules(file, HandleEdited::PreferOriginal).unwrap();
+        assert_eq!(takeout.edited_assets().count(), 0);
+    }
+
+    #[test]
+    fn multiple_parts() {
+        let part1 = archive_file(
+            "multiple_parts_1",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg.json",
+                    br#"{"photoTakenTime": {"timestamp": "1370762069"}}"#,
+                ),
+                ("Takeout/Google Photos/Holiday/IMG_2.jpg", b"other"),
+            ],
+        );
+        let part2 = archive_file(
+            "multiple_parts_2",
+            &[
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"original"),
+                ("Takeout/Google Photos/Wedding/IMG_3.jpg", b"third"),
+            ],
+        );
+        let mut takeout =
+            Takeout::from_parts(vec![part1, part2], HandleEdited::PreferEdited).unwrap();
+
+        assert_eq!(takeout.len(), 3);
+        let albums = takeout.albums();
+        assert_eq!(albums.len(), 2);
+        let mut holiday = albums
+            .iter()
+            .find(|(name, _)| **name == "Holiday")
+            .unwrap()
+            .1
+            .clone();
+        holiday.sort();
+        assert_eq!(holiday, vec!["IMG_1.jpg", "IMG_2.jpg"]);
+
+        let mut records: Vec<(String, Option<OffsetDateTime>, usize)> = takeout
+            .records()
+            .unwrap()
+            .map(|record| {
+                let record = record.unwrap();
+                (
+                    record.name().to_string(),
+                    record.date_taken(),
+                    record.albums().len(),
+                )
+            })
+            .collect();
+        records.sort();
+        assert_eq!(
+            records,
+            vec![
+                (
+                    "IMG_1.jpg".to_string(),
+                    Some(OffsetDateTime::from_unix_timestamp(1370762069).unwrap()),
+                    1
+                ),
+                ("IMG_2.jpg".to_string(), None, 1),
+                ("IMG_3.jpg".to_string(), None, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn records_from() {
+        let files: [(&str, &[u8]); 3] = [
+            ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"first"),
+            ("Takeout/Google Photos/Holiday/IMG_2.jpg", b"second"),
+            ("Takeout/Google Photos/Holiday/IMG_3.jpg", b"third"),
+        ];
+        let mut takeout = Takeout::new(archive_file("records_from", &files)).unwrap();
+        let records: Vec<(String, Vec<u8>)> = takeout
+            .records_from(1)
+            .unwrap()
+            .map(|record| {
+                let mut record = record.unwrap();
+                (record.name().to_string(), record.data())
+            })
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                ("IMG_2.jpg".to_string(), b"second".to_vec()),
+                ("IMG_3.jpg".to_string(), b"third".to_vec())
+            ]
+        );
+
+        let mut takeout = Takeout::new(archive_file("records_from_end", &files)).unwrap();
+        assert_eq!(takeout.records_from(5).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn entries_all() {
+        let file = archive_file(
+            "entries_all",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg.json",
+                    br#"{"photoTakenTime": {"timestamp": "1370762069"}}"#,
+                ),
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"original"),
+                ("Takeout/Google Photos/Holiday/notes.txt", b"notes"),
+            ],
+        );
+        let mut takeout = Takeout::new(file).unwrap();
+
+        let items: Vec<String> = takeout
+            .entries_all()
+            .unwrap()
+            .map(|item| match item.unwrap() {
+                ArchiveItem::Media(record) => format!("media {}", record.name()),
+                ArchiveItem::Metadata { name, json } => format!("metadata {name} {}", json.len()),
+                ArchiveItem::Unknown { path } => format!("unknown {path}"),
+            })
+            .collect();
+        assert_eq!(
+            items,
+            vec![
+                "metadata IMG_1.jpg 47",
+                "media IMG_1.jpg",
+                "unknown Takeout/Google Photos/Holiday/notes.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn shared_albums() {
+        let file = archive_file(
+            "shared_albums",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/metadata.json",
+                    br#"{"title": "Holiday", "access": "protected"}"#,
+                ),
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"first"),
+                (
+                    "Takeout/Google Photos/Wedding/metadata.json",
+                    br#"{"title": "Wedding"}"#,
+                ),
+                ("Takeout/Google Photos/Wedding/IMG_2.jpg", b"second"),
+            ],
+        );
+        let takeout = Takeout::new(file).unwrap();
+        assert_eq!(takeout.len(), 2);
+
+        let albums = takeout.albums();
+        assert_eq!(albums.len(), 2);
+        assert!(albums.is_shared("Holiday"));
+        assert!(!albums.is_shared("Wedding"));
+        assert!(albums.contains_key("Wedding"));
+        assert!(!albums.contains_key("Birthday"));
+        assert_eq!(albums.get("Holiday"), Some(&vec!["IMG_1.jpg"]));
+        assert_eq!((&albums).into_iter().count(), 2);
+    }
+
+    #[test]
+    fn album_names_with_whitespace() {
+        let file = archive_file(
+            "album_names_with_whitespace",
+            &[
+                ("Takeout/Google Photos/ Vacation/IMG_1.jpg", b"first"),
+                ("Takeout/Google Photos/Vacation /IMG_2.jpg", b"second"),
+                ("Takeout/Google Photos/Summer  Vacation/IMG_3.jpg", b"third"),
+            ],
+        );
+        let takeout = Takeout::new(file).unwrap();
+
+        let albums = takeout.albums();
+        let mut counts: Vec<(&str, usize)> = albums.counts().collect();
+        counts.sort();
+        assert_eq!(counts, vec![("Summer Vacation", 1), ("Vacation", 2)]);
+    }
+
+    #[test]
+    fn partner_shared_albums() {
+        let files: [(&str, &[u8]); 7] = [
+            ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"first"),
+            (
+                "Takeout/Google Photos/Shared albums/Holiday/metadata.json",
+                br#"{"title": "Holiday", "access": "protected"}"#,
+            ),
+            (
+                "Takeout/Google Photos/Shared albums/Holiday/IMG_2.jpg.json",
+                br#"{"photoTakenTime": {"timestamp": "1370762069"}}"#,
+            ),
+            (
+                "Takeout/Google Photos/Shared albums/Holiday/IMG_2.jpg",
+                b"second",
+            ),
+            (
+                "Takeout/Google Photos/Shared albums/Wedding/IMG_3.jpg",
+                b"third",
+            ),
+            (
+                "Takeout/Google Photos/Shared albums/Trash/IMG_4.jpg",
+                b"fourth",
+            ),
+            (
+                "Takeout/Google Photos/Family/Shared albums/IMG_5.jpg",
+                b"fifth",
+            ),
+        ];
+        let takeout = Takeout::new(archive_file("partner_shared_albums", &files)).unwrap();
+        assert_eq!(takeout.len(), 5);
+
+        let albums = takeout.albums();
+        let mut counts: Vec<(&str, usize)> = albums.counts().collect();
+        counts.sort();
+        assert_eq!(
+            counts,
+            vec![
+                ("Holiday", 1),
+                ("Shared albums", 1),
+                ("Shared: Holiday", 1),
+                ("Shared: Trash", 1),
+                ("Shared: Wedding", 1)
+            ]
+        );
+        assert!(albums.is_shared("Shared: Holiday"));
+        assert!(albums.is_shared("Shared: Wedding"));
+        assert!(!albums.is_shared("Holiday"));
+        assert!(!albums.is_shared("Shared albums"));
+
+        let config = TakeoutConfig::default().shared_album_prefix("Partner - ");
+        let takeout =
+            Takeout::with_config(archive_file("partner_shared_prefix", &files), config).unwrap();
+        let albums = takeout.albums();
+        assert_eq!(albums.get("Partner - Wedding"), Some(&vec!["IMG_3.jpg"]));
+        assert!(albums.is_shared("Partner - Holiday"));
+        assert!(!albums.contains_key("Shared: Wedding"));
+    }
+
+    #[test]
+    fn nested_album_folders() {
+        let file = archive_file(
+            "nested_album_folders",
+            &[
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"first"),
+                ("Takeout/Google Photos/Family/Wedding/IMG_2.jpg", b"second"),
+            ],
+        );
+        let takeout = Takeout::new(file).unwrap();
+
+        let albums = takeout.albums();
+        let mut counts: Vec<(&str, usize)> = albums.counts().collect();
+        counts.sort();
+        assert_eq!(counts, vec![("Holiday", 1), ("Wedding", 1)]);
+    }
+
+    #[test]
+    fn config_extensions() {
+        let files: [(&str, &[u8]); 3] = [
+            ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"image"),
+            ("Takeout/Google Photos/Holiday/VID_1.MKV", b"video"),
+            (
+                "Takeout/Google Photos/Holiday/VID_1.MKV.json",
+                br#"{"photoTakenTime": {"timestamp": "1370762069"}}"#,
+            ),
+        ];
+        let takeout = Takeout::new(archive_file("config_default", &files)).unwrap();
+        assert_eq!(takeout.len(), 1);
+
+        let config = TakeoutConfig::default().extension(".mkv", AssetType::Video);
+        let mut takeout =
+            Takeout::with_config(archive_file("config_extensions", &files), config).unwrap();
+        assert_eq!(takeout.len(), 2);
+        assert_eq!(takeout.assets_missing_metadata(), vec!["IMG_1.jpg"]);
+
+        let mut types: Vec<(String, AssetType)> = takeout
+            .records()
+            .unwrap()
+            .map(|record| {
+                let record = record.unwrap();
+                (record.name().to_string(), record.asset_type())
+            })
+            .collect();
+        types.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            types,
+            vec![
+                ("IMG_1.jpg".to_string(), AssetType::Image),
+                ("VID_1.MKV".to_string(), AssetType::Video),
+            ]
+        );
+    }
+
+    #[test]
+    fn archive_and_trash_folders() {
+        let file = archive_file(
+            "archive_and_trash_folders",
+            &[
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", b"first"),
+                ("Takeout/Google Photos/Archive/IMG_1.jpg", b"first"),
+                ("Takeout/Google Photos/Trash/IMG_2.jpg", b"second"),
+                ("Takeout/Google Photos/Holiday/IMG_3.jpg", b"third"),
+            ],
+        );
+        let mut takeout = Takeout::new(file).unwrap();
+
+        let albums = takeout.albums();
+        assert_eq!(albums.len(), 1);
+        assert!(albums.iter().all(|(name, _)| *name == "Holiday"));
+
+        let mut records: Vec<(String, bool, bool)> = takeout
+            .records()
+            .unwrap()
+            .map(|record| {
+                let record = record.unwrap();
+                (
+                    record.name().to_string(),
+                    record.archived(),
+                    record.trashed(),
+                )
+            })
+            .collect();
+        records.sort();
+        records.dedup();
+        assert_eq!(
+            records,
+            vec![
+                ("IMG_1.jpg".to_string(), true, false),
+                ("IMG_2.jpg".to_string(), false, true),
+                ("IMG_3.jpg".to_string(), false, false),
+            ]
+        );
+        assert!(takeout.media("IMG_1-edited.jpg").unwrap().archived());
+    }
+
+    #[test]
+    fn embedded_motion() {
+        let mut motion_photo = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        motion_photo.extend_from_slice(&[0; 100_000]);
+        motion_photo.extend_from_slice(b"\0\0\x01\x00moov\0\0\0\x6cmvhd");
+        let file = archive_file(
+            "embedded_motion",
+            &[
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", &motion_photo),
+                ("Takeout/Google Photos/Holiday/IMG_2.jpg", b"\xFF\xD8\xFF"),
+                (
+                    "Takeout/Google Photos/Holiday/VID_3.mp4",
+                    b"\0\0\0\x18ftypmp42moov",
+                ),
+            ],
+        );
+        let mut takeout = Takeout::new(file).unwrap();
+
+        let records: Vec<(String, bool, usize)> = takeout
+            .records()
+            .unwrap()
+            .map(|record| {
+                let mut record = record.unwrap();
+                let motion = record.has_embedded_motion();
+                (record.name().to_string(), motion, record.data().len())
+            })
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                ("IMG_1.jpg".to_string(), true, motion_photo.len()),
+                ("IMG_2.jpg".to_string(), false, 3),
+                ("VID_3.mp4".to_string(), false, 16),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreadable_record() {
+        let mut image = b"\xFF\xD8\xFF".to_vec();
+        image.extend((0..100_000u32).map(|i| (i * 7919 % 251) as u8));
+        let bytes = archive_bytes(&[("Takeout/Google Photos/Holiday/IMG_1.jpg", &image)]);
+        let takeout = Takeout::new(io::Cursor::new(bytes.clone())).unwrap();
+
+        let mut truncated = Archive::new(GzDecoder::new(&bytes[..bytes.len() / 2]));
+        let entry = truncated.entries().unwrap().next().unwrap().unwrap();
+        let mut record = Record::new(
+            takeout.media("IMG_1.jpg").unwrap(),
+            "IMG_1.jpg",
+            entry,
+            &TakeoutConfig::default(),
+        );
+
+        assert!(!record.has_embedded_motion());
+        assert!(record.read_to_end(&mut Vec::new()).is_err());
+        assert!(crate::Asset::try_from(record).is_err());
+    }
+
+    #[test]
+    fn in_memory_archive() {
+        let data = io::Cursor::new(archive_bytes(&FILES));
+        let mut takeout = Takeout::new(data).unwrap();
+        assert_eq!(takeout.len(), 2);
+
+        let records: Vec<(String, Vec<u8>)> = takeout
+            .records()
+            .unwrap()
+            .map(|record| {
+                let mut record = record.unwrap();
+                (record.name().to_string(), record.data())
+            })
+            .collect();
+        assert_eq!(
+            records,
+            vec![
+                ("IMG_1.jpg".to_string(), b"edited".to_vec()),
+                ("IMG_2.jpg".to_string(), b"other".to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_progress() {
+        let data = archive_bytes(&FILES);
+        let size = data.len() as u64;
+        let mut reports: Vec<ScanProgress> = Vec::new();
+        let takeout = Takeout::with_progress(
+            vec![io::Cursor::new(data)],
+            HandleEdited::PreferEdited,
+            |progress| reports.push(progress.clone()),
+        )
+        .unwrap();
+
+        assert_eq!(takeout.len(), 2);
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[2].entries(), 3);
+        assert!(reports
+            .windows(2)
+            .all(|pair| pair[0].bytes_read() <= pair[1].bytes_read()));
+        assert!(reports[2].bytes_read() > 0);
+        assert!(reports[2].bytes_read() <= size);
+        assert_eq!(reports[2].total_bytes(), size);
+    }
+
+    #[test]
+    fn truncated_archive() {
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let path = archive(
+            "truncated_archive",
+            &[
+                ("Takeout/Google Photos/Holiday/IMG_1.jpg", &data),
+                ("Takeout/Google Photos/Holiday/IMG_2.jpg", &data),
+            ],
+        );
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() / 2]).unwrap();
+
+        match Takeout::new(File::open(&path).unwrap()) {
+            Err(err @ ParseError::IoAt { .. }) => assert_eq!(
+                err.to_string(),
+                "IO error reading Takeout/Google Photos/Holiday/IMG_1.jpg"
+            ),
+            Err(err) => panic!("unexpected error: {err}"),
+            Ok(_) => panic!("truncated archive must not parse"),
+        }
+    }
+
+    #[test]
+    fn empty_archive_is_not_uploaded() {
+        let path = archive(
+            "empty_archive",
+            &[("Takeout/Google Photos/Holiday/notes.txt", b"no media")],
+        );
+        let mut uploader = Uploader::new(path).unwrap();
+        assert!(uploader.is_empty());
+
+        let client = crate::Client::new(
+            "https://immich.example.com/api".try_into().unwrap(),
+            crate::auth::Authenticated::ApiKey("s3cr3t".to_string()),
+            Vec::new(),
+            ureq::agent(),
+        );
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        assert!(matches!(
+            uploader.upload(&client, 2, sender, |_| true),
+            Err(crate::ImmichError::EmptyTakeoutArchive)
+        ));
+    }
+
+    #[test]
+    fn upload_images_only() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let path = archive(
+            "upload_images_only",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg",
+                    b"\xFF\xD8\xFFimage",
+                ),
+                ("Takeout/Google Photos/Holiday/VID_1.mp4", b"video"),
+            ],
+        );
+        let mut uploader = Uploader::new(path).unwrap();
+        assert_eq!(uploader.len(), 2);
+
+        let uploads = Arc::new(AtomicUsize::new(0));
+        let counter = uploads.clone();
+        let album = album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 0);
+        let client = crate::Client::mock(move |method, path| match (method, path) {
+            ("POST", "/assets") => {
+                counter.fetch_add(1, Ordering::SeqCst);
+                (
+                    201,
+                    r#"{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "status": "created"}"#
+                        .to_string(),
+                )
+            }
+            ("GET", "/albums") => (200, "[]".to_string()),
+            ("POST", "/albums") => (201, album.to_string()),
+            ("PUT", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets") => (
+                200,
+                r#"[{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": true}]"#.to_string(),
+            ),
+            ("GET", "/users/me") => (200, user_json()),
+            _ => (404, String::new()),
+        });
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        uploader.upload_images_only(&client, 2, sender).unwrap();
+
+        assert_eq!(uploads.load(Ordering::SeqCst), 1);
+        let uploaded: Vec<crate::upload::Uploaded> = receiver.iter().collect();
+        assert_eq!(uploaded.len(), 1);
+        assert_eq!(uploaded[0].device_asset_id(), "IMG_1.jpg");
+    }
+
+    #[test]
+    fn stack_edited_versions() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let path = archive(
+            "stack_edited_versions",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg",
+                    b"\xFF\xD8\xFForiginal",
+                ),
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1-edited.jpg",
+                    b"\xFF\xD8\xFFedited",
+                ),
+                (
+                    "Takeout/Google Photos/Holiday/IMG_2.jpg",
+                    b"\xFF\xD8\xFFother",
+                ),
+            ],
+        );
+        let mut uploader = Uploader::new(path).unwrap();
+        assert_eq!(uploader.len(), 2);
+        uploader.keep_originals().stack_edited(true);
+        assert_eq!(uploader.len(), 3);
+
+        let uploads = Arc::new(AtomicUsize::new(0));
+        let stacks = Arc::new(AtomicUsize::new(0));
+        let upload_counter = uploads.clone();
+        let stack_counter = stacks.clone();
+        let album = album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 0);
+        let client = crate::Client::mock(move |method, path| match (method, path) {
+            ("POST", "/assets") => {
+                let count = upload_counter.fetch_add(1, Ordering::SeqCst);
+                (
+                    201,
+                    format!(
+                        r#"{{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc{count}", "status": "created"}}"#
+                    ),
+                )
+            }
+            ("POST", "/stacks") => {
+                stack_counter.fetch_add(1, Ordering::SeqCst);
+                (
+                    201,
+                    r#"{"id": "6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e",
+                        "primaryAssetId": "41a3a296-7e86-4eb4-8e44-aead03344fc0",
+                        "assets": []}"#
+                        .to_string(),
+                )
+            }
+            ("GET", "/albums") => (200, "[]".to_string()),
+            ("POST", "/albums") => (201, album.to_string()),
+            ("PUT", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets") => {
+                (200, "[]".to_string())
+            }
+            ("GET", "/users/me") => (200, user_json()),
+            _ => (404, String::new()),
+        });
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        uploader.upload(&client, 2, sender, |_| true).unwrap();
+
+        assert_eq!(uploads.load(Ordering::SeqCst), 3);
+        assert_eq!(stacks.load(Ordering::SeqCst), 1);
+        let mut names: Vec<String> = receiver
+            .iter()
+            .map(|uploaded| uploaded.device_asset_id().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["IMG_1-edited.jpg", "IMG_1.jpg", "IMG_2.jpg"]);
+    }
+
+    #[test]
+    fn reimport_skips_album_add() {
+        use crate::upload::Status;
+        use crate::AssetMoveError;
+        use std::sync::{Arc, Mutex};
+
+        let path = archive(
+            "reimport_skips_album_add",
+            &[(
+                "Takeout/Google Photos/Holiday/IMG_1.jpg",
+                b"\xFF\xD8\xFFimage",
+            )],
+        );
+
+        // Returns a mock server with an existing "Holiday" album that contains the asset
+        let server = |status: &'static str, requests: Arc<Mutex<Vec<String>>>| {
+            crate::Client::mock(move |method, path| {
+                requests.lock().unwrap().push(format!("{method} {path}"));
+                match (method, path) {
+                    ("POST", "/assets") => (
+                        200,
+                        format!(r#"{{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "status": "{status}"}}"#),
+                    ),
+                    ("GET", "/albums") => (
+                        200,
+                        format!("[{}, {}]", album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 1), album_json("6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e", "Google Takout Import", 1)),
+                    ),
+                    ("GET", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => (
+                        200,
+                        r#"{"assets": [{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "type": "IMAGE"}]}"#
+                            .to_string(),
+                    ),
+                    ("PUT", _) => (
+                        200,
+                        r#"[{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": true}]"#
+                            .to_string(),
+                    ),
+                    _ => (404, String::new()),
+                }
+            })
+        };
+
+        // newly created assets are added without checking the album content
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = server("created", requests.clone());
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        uploader.upload(&client, 1, sender, |_| true).unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(requests
+            .iter()
+            .all(|request| request != "GET /albums/f0edb589-1312-4161-b41e-0a18f127b3dd"));
+        assert!(requests
+            .iter()
+            .any(|request| request == "PUT /albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets"));
+
+        // existing assets that are in the album already are not added again
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = server("duplicate", requests.clone());
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        let imported = uploader.upload(&client, 1, sender, |_| true).unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(requests
+            .iter()
+            .all(|request| request != "PUT /albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets"));
+        assert!(imported.assets().iter().all(|moved| moved.success()));
+
+        // existing assets are not added to any album if the library is left as it is
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = server("duplicate", requests.clone());
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        uploader.duplicate_album_policy(DuplicateAlbumPolicy::LeaveAsIs);
+        let imported = uploader.upload(&client, 1, sender, |_| true).unwrap();
+        let requests = requests.lock().unwrap();
+        assert!(requests
+            .iter()
+            .all(|request| !request.starts_with("GET /albums/f0edb589")));
+        assert!(requests
+            .iter()
+            .all(|request| request != "PUT /albums/f0edb589-1312-4161-b41e-0a18f127b3dd/assets"));
+        assert_eq!(imported.assets().len(), 1);
+        let moved = &imported.assets()[0];
+        assert!(moved.success());
+        assert_eq!(moved.error(), &Some(AssetMoveError::Duplicate));
+        assert_eq!(moved.upload_status(), Some(&Status::Duplicate));
+        assert_eq!(imported.summary().duplicate(), 1);
+
+        // assets excluded by the filter are accounted for as well
+        let client = server("duplicate", Arc::new(Mutex::new(Vec::new())));
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        let imported = uploader.upload(&client, 1, sender, |_| false).unwrap();
+        assert_eq!(imported.summary().scanned(), 1);
+        assert_eq!(imported.summary().attempted(), 0);
+        assert_eq!(imported.summary().unreadable(), 0);
+    }
+
+    #[test]
+    fn summary_counts_records() {
+        let path = archive(
+            "summary_counts_records",
+            &[
+                (
+                    "Takeout/Google Photos/Photos from 2019/IMG_1.jpg",
+                    b"\xFF\xD8\xFFimage",
+                ),
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg",
+                    b"\xFF\xD8\xFFimage",
+                ),
+            ],
+        );
+        let client = crate::Client::mock(|method, path| match (method, path) {
+            ("POST", "/assets") => (
+                201,
+                r#"{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "status": "created"}"#
+                    .to_string(),
+            ),
+            ("GET", "/albums") => (200, "[]".to_string()),
+            ("GET", "/users/me") => (200, user_json()),
+            ("POST", "/albums") => (
+                201,
+                album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Album", 0),
+            ),
+            ("PUT", _) => (
+                200,
+                r#"[{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": true}]"#.to_string(),
+            ),
+            _ => (404, String::new()),
+        });
+
+        let mut uploader = Uploader::new(&path).unwrap();
+        assert_eq!(uploader.len(), 1);
+
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let imported = uploader.upload(&client, 1, sender, |_| false).unwrap();
+        assert_eq!(
+            imported.summary().to_string(),
+            "Scanned: 2 | Attempted: 0 | Created: 0 | Duplicate: 0 | Failure: 0 | Unreadable: 0"
+        );
+
+        // both records belong to the album, as the photo is in it
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        let imported = uploader
+            .upload_albums(&client, 1, sender, &["Holiday"])
+            .unwrap();
+        assert_eq!(
+            imported.summary().to_string(),
+            "Scanned: 2 | Attempted: 2 | Created: 2 | Duplicate: 0 | Failure: 0 | Unreadable: 0"
+        );
+
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        let imported = uploader
+            .upload_albums(&client, 1, sender, &["Wedding"])
+            .unwrap();
+        assert_eq!(imported.summary().scanned(), 2);
+        assert_eq!(imported.summary().attempted(), 0);
+    }
+
+    #[test]
+    fn skip_existing() {
+        use std::sync::{Arc, Mutex};
+
+        let path = archive(
+            "skip_existing",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg",
+                    b"\xFF\xD8\xFFfirst",
+                ),
+                (
+                    "Takeout/Google Photos/Holiday/IMG_2.jpg",
+                    b"\xFF\xD8\xFFsecond",
+                ),
+            ],
+        );
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let client = crate::Client::mock(move |method, path| {
+            log.lock().unwrap().push(format!("{method} {path}"));
+            match (method, path) {
+                ("POST", "/assets/bulk-upload-check") => (
+                    200,
+                    r#"{"results": [
+                        {"id": "0", "action": "reject", "assetId": "41a3a296-7e86-4eb4-8e44-aead03344fc9"},
+                        {"id": "1", "action": "reject", "assetId": "5d1c2b3a-4e5f-4a6b-8c7d-9e0f1a2b3c4d"}
+                    ]}"#
+                    .to_string(),
+                ),
+                ("GET", "/albums") => (
+                    200,
+                    format!("[{}]", album_json("6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e", "Google Takout Import", 2)),
+                ),
+                ("GET", "/albums/f0edb589-1312-4161-b41e-0a18f127b3dd") => {
+                    (200, r#"{"assets": []}"#.to_string())
+                }
+                ("POST", "/albums") => (
+                    201,
+                    album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Holiday", 0),
+                ),
+                ("GET", "/users/me") => (200, user_json()),
+                ("PUT", _) => (
+                    200,
+                    r#"[{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": true},
+                        {"id": "5d1c2b3a-4e5f-4a6b-8c7d-9e0f1a2b3c4d", "success": true}]"#
+                        .to_string(),
+                ),
+                _ => (404, String::new()),
+            }
+        });
+
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        uploader.skip_existing(true);
+        let imported = uploader.upload(&client, 1, sender, |_| true).unwrap();
+
+        assert_eq!(imported.summary().duplicate(), 2);
+        let requests = requests.lock().unwrap();
+        assert!(requests.iter().all(|request| request != "POST /assets"));
+        // the skipped assets are added to the import album
+        assert!(requests
+            .iter()
+            .any(|request| request == "PUT /albums/6f2a8a5c-0d6b-4e8b-9d51-8c0b2a1c7f3e/assets"));
+    }
+
+    #[test]
+    fn album_progress() {
+        let path = archive(
+            "album_progress",
+            &[
+                (
+                    "Takeout/Google Photos/Holiday/IMG_1.jpg",
+                    b"\xFF\xD8\xFFimage",
+                ),
+                (
+                    "Takeout/Google Photos/Wedding/IMG_2.jpg",
+                    b"\xFF\xD8\xFFother",
+                ),
+            ],
+        );
+        let client = crate::Client::mock(|method, path| match (method, path) {
+            ("POST", "/assets") => (
+                201,
+                r#"{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "status": "created"}"#
+                    .to_string(),
+            ),
+            ("GET", "/albums") => (200, "[]".to_string()),
+            ("GET", "/users/me") => (200, user_json()),
+            ("POST", "/albums") => (
+                201,
+                album_json("f0edb589-1312-4161-b41e-0a18f127b3dd", "Album", 0),
+            ),
+            ("PUT", _) => (
+                200,
+                r#"[{"id": "41a3a296-7e86-4eb4-8e44-aead03344fc9", "success": true}]"#.to_string(),
+            ),
+            _ => (404, String::new()),
+        });
+
+        let (sender, _receiver) = crossbeam_channel::unbounded();
+        let (album_sender, album_receiver) = crossbeam_channel::unbounded();
+        let mut uploader = Uploader::new(&path).unwrap();
+        uploader.album_progress(album_sender);
+        let imported = uploader.upload(&client, 1, sender, |_| true).unwrap();
+        drop(uploader);
+        assert_eq!(
+            imported.summary().to_string(),
+            "Scanned: 2 | Attempted: 2 | Created: 2 | Duplicate: 0 | Failure: 0 | Unreadable: 0"
+        );
+
+        let mut progress: Vec<AlbumProgress> = album_receiver.iter().collect();
+        progress.sort_by_key(|progress| progress.done());
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[0].done(), 1);
+        assert_eq!(progress[1].done(), 2);
+        assert!(progress.iter().all(|progress| progress.total() == 2));
+        assert!(progress.iter().all(|progress| progress.moved() == 1));
+        assert!(progress.iter().all(|progress| progress.failed() == 0));
+        let mut names: Vec<&str> = progress.iter().map(AlbumProgress::album).collect();
+        names.sort();
+        assert_eq!(names, ["Holiday", "Wedding"]);
     }
 }
@@ -0,0 +1,120 @@
+use super::HandleEdited;
+use crate::AssetType;
+use std::path::Path;
+
+/// Name of the album that contains all imported images and videos
+const IMPORT_ALBUM: &str = "Google Takout Import";
+
+/// Prefix of the names of albums that were shared with the user
+const SHARED_ALBUM_PREFIX: &str = "Shared: ";
+
+/// Defines how the Google Takeout archive is parsed and imported
+///
+/// The default configuration prefers edited files (see [`HandleEdited::PreferEdited`]),
+/// uses the built-in list of image and video extensions, adds all imported assets
+/// to the "Google Takout Import" album and prefixes the names of shared albums with
+/// "Shared: ".
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::fs::File;
+/// use immich::takeout::{HandleEdited, Takeout, TakeoutConfig};
+/// use immich::AssetType;
+///
+/// let config = TakeoutConfig::default()
+///     .edited_files(HandleEdited::UseBoth)
+///     .extension("mkv", AssetType::Video)
+///     .import_album("Google Photos");
+///
+/// let file = File::open("path/to/archive.tar.gz").unwrap();
+/// let archive = Takeout::with_config(file, config).unwrap();
+///
+/// println!("The archive contains {} images and videos", archive.len());
+/// ```
+#[derive(Debug)]
+pub struct TakeoutConfig {
+    edited_files: HandleEdited,
+    extensions: Vec<(String, AssetType)>,
+    import_album: String,
+    shared_album_prefix: String,
+}
+
+impl Default for TakeoutConfig {
+    fn default() -> Self {
+        Self {
+            edited_files: HandleEdited::PreferEdited,
+            extensions: Vec::new(),
+            import_album: IMPORT_ALBUM.to_string(),
+            shared_album_prefix: SHARED_ALBUM_PREFIX.to_string(),
+        }
+    }
+}
+
+impl TakeoutConfig {
+    /// Sets the handling of files that have an edited version (see [`HandleEdited`])
+    pub fn edited_files(mut self, edited_files: HandleEdited) -> Self {
+        self.edited_files = edited_files;
+        self
+    }
+
+    /// Treats files with the extension as assets of the [`AssetType`], in addition to the
+    /// built-in extensions
+    ///
+    /// The extension is matched case-insensitive and without the leading dot, e.g. `"mkv"`.
+    /// It takes precedence over the built-in extensions.
+    pub fn extension(mut self, extension: &str, asset_type: AssetType) -> Self {
+        self.extensions
+            .push((extension.trim_start_matches('.').to_lowercase(), asset_type));
+        self
+    }
+
+    /// Sets the name of the album that contains all imported images and videos
+    pub fn import_album(mut self, name: &str) -> Self {
+        self.import_album = name.to_string();
+        self
+    }
+
+    /// Sets the prefix of the names of albums in the `Shared albums` folder
+    ///
+    /// Albums that a partner or another account shared with the user are exported to
+    /// `Google Photos/Shared albums/`. The prefix keeps them apart from own albums with
+    /// the same name, an empty prefix merges them.
+    pub fn shared_album_prefix(mut self, prefix: &str) -> Self {
+        self.shared_album_prefix = prefix.to_string();
+        self
+    }
+
+    pub(crate) fn handle_edited(&self) -> &HandleEdited {
+        &self.edited_files
+    }
+
+    pub(crate) fn set_handle_edited(&mut self, edited_files: HandleEdited) {
+        self.edited_files = edited_files;
+    }
+
+    pub(crate) fn extensions(&self) -> &[(String, AssetType)] {
+        &self.extensions
+    }
+
+    /// Derives the [`AssetType`] from the extension of the path
+    pub(crate) fn asset_type(&self, path: &Path) -> AssetType {
+        let Some(ext) = path.extension() else {
+            return AssetType::Unknown;
+        };
+        let ext = ext.to_string_lossy().to_lowercase();
+        self.extensions
+            .iter()
+            .find(|(extension, _)| extension == &ext)
+            .map(|(_, asset_type)| asset_type.clone())
+            .unwrap_or_else(|| AssetType::from_extension(&ext))
+    }
+
+    pub(crate) fn import_album_name(&self) -> &str {
+        &self.import_album
+    }
+
+    pub(crate) fn shared_prefix(&self) -> &str {
+        &self.shared_album_prefix
+    }
+}
@@ -1,21 +1,71 @@
-use std::{borrow::Cow, fs::File, path::Path};
+use std::{io::Read, path::Path};
 
-use flate2::read::GzDecoder;
 use tar::Entry;
 
-use crate::takeout::ParseError;
+use crate::takeout::{ParseError, TakeoutConfig};
+use crate::AssetType;
 
 #[derive(Eq, Hash, PartialEq)]
 pub(crate) struct Filename {
     name: String,
     album: String,
+    folder: Folder,
     filetype: FileType,
 }
 
+/// The kind of folder that contains a file
+#[derive(Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Folder {
+    /// A regular album
+    Album,
+    /// The special `Archive` folder of archived photos
+    Archive,
+    /// The special `Trash` folder of deleted photos
+    Trash,
+    /// An album in the `Shared albums` folder, shared by a partner or another account
+    Shared,
+}
+
+impl From<&str> for Folder {
+    fn from(name: &str) -> Self {
+        match name {
+            "Archive" => Self::Archive,
+            "Trash" => Self::Trash,
+            _ => Self::Album,
+        }
+    }
+}
+
+impl Folder {
+    /// The folder of the Takeout archive that contains all albums
+    const ROOT: &'static str = "Google Photos";
+
+    /// The folder in `Google Photos/` that contains the albums shared with the user
+    const SHARED_ROOT: &'static str = "Shared albums";
+
+    /// Derives the kind of folder from the path of a file in the folder
+    ///
+    /// Only the `Archive` and `Trash` folders directly in `Google Photos/` are special,
+    /// folders with the same name elsewhere are regular albums. Folders directly in
+    /// `Google Photos/Shared albums/` are shared albums.
+    pub fn from_path(path: &Path, album: &str) -> Self {
+        let mut parents = path.ancestors().skip(2).map(Path::file_name);
+        match (parents.next().flatten(), parents.next().flatten()) {
+            (Some(root), _) if root == Self::ROOT => Self::from(album),
+            (Some(shared), Some(root)) if shared == Self::SHARED_ROOT && root == Self::ROOT => {
+                Self::Shared
+            }
+            _ => Self::Album,
+        }
+    }
+}
+
 impl Filename {
-    const MEDIA_EXTENSIONS: [&'static str; 10] = [
-        "jpg", "jpeg", "png", "webp", "heic", "mp4", "m4v", "webm", "3gp", "gif",
-    ];
+    /// Returns true for the extensions of all [`AssetType`]s and the additional `extensions`
+    fn is_media(ext: &str, extensions: &[(String, AssetType)]) -> bool {
+        AssetType::from_extension(ext) != AssetType::Unknown
+            || extensions.iter().any(|(extension, _)| extension == ext)
+    }
 
     pub fn name(&self) -> &str {
         &self.name
@@ -25,10 +75,58 @@ impl Filename {
         &self.album
     }
 
+    pub fn folder(&self) -> &Folder {
+        &self.folder
+    }
+
     pub fn filetype(&self) -> &FileType {
         &self.filetype
     }
 
+    /// Returns true if this is the `metadata.json` of an album, not of an image or video
+    ///
+    /// The name of the album metadata file is localized, but it never contains
+    /// the extension of an image or video.
+    pub fn is_album_metadata(&self, extensions: &[(String, AssetType)]) -> bool {
+        self.filetype == FileType::Metadata
+            && !Path::new(&self.name).extension().is_some_and(|ext| {
+                Self::is_media(&ext.to_string_lossy().to_lowercase(), extensions)
+            })
+    }
+
+    /// Re-adds the `-edited` suffix to a normalized name
+    ///
+    /// IMG_20131023_123651.jpg ==> IMG_20131023_123651-edited.jpg
+    pub fn edited_name(name: &str) -> String {
+        match name.rfind('.') {
+            Some(idx) => format!("{}-edited{}", &name[..idx], &name[idx..]),
+            None => format!("{name}-edited"),
+        }
+    }
+
+    /// Normalizes the filename of media and metadata files to the same name
+    ///
+    /// IMG_20131023_123651(1).jpg ==> IMG_20131023_123651(1).jpg
+    /// IMG_20131023_123651(1)-edited.jpg ==> IMG_20131023_123651(1).jpg
+    /// IMG_20131023_123651.jpg(1).json ==> IMG_20131023_123651(1).jpg
+    /// IMG_20131023_123651.jpg.supplemental-metadata(1).json ==> IMG_20131023_123651(1).jpg
+    pub fn normalize(file_name: &str) -> String {
+        let mut name = file_name
+            .replace("-edited", "")
+            .replace(".supplemental-metadata", "")
+            .replace(".json", "");
+        Self::normalize_duplicates(&mut name);
+        name
+    }
+
+    /// Trims the album name and collapses whitespace, so that near-identical folder names
+    /// end up in the same album
+    ///
+    /// " Summer  Vacation " ==> "Summer Vacation"
+    pub fn normalize_album(album: &str) -> String {
+        album.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
     pub fn normalize_duplicates(name: &mut String) {
         // this happens only for metadata
         // IMG_20131023_123651(1).jpg
@@ -45,40 +143,50 @@ impl Filename {
             }
         }
     }
-}
 
-impl TryFrom<&Entry<'_, GzDecoder<File>>> for Filename {
-    type Error = ParseError;
-    fn try_from(entry: &Entry<GzDecoder<File>>) -> Result<Self, Self::Error> {
+    /// Parses the path of an archive entry
+    ///
+    /// Files with one of the additional extensions of the `config` are handled as images
+    /// or videos. The names of shared albums start with the shared album prefix of the
+    /// `config`, to keep them apart from own albums with the same name.
+    pub fn from_entry<R: Read>(
+        entry: &Entry<'_, R>,
+        config: &TakeoutConfig,
+    ) -> Result<Self, ParseError> {
         let path = entry.path()?;
 
-        let filetype = FileType::try_from(&path)?;
-
-        let album = path
-            .parent()
-            .ok_or(ParseError::FilePathError(
-                "Asset path must contain an album name".to_string(),
-            ))?
-            .file_name()
-            .ok_or(ParseError::FilePathError(
-                "Asset path must contain an album name".to_string(),
-            ))?
-            .to_string_lossy()
-            .to_string();
-
-        let mut name = path
-            .file_name()
-            .ok_or(ParseError::FilePathError(
-                "Asset path must contain a filename".to_string(),
-            ))?
-            .to_string_lossy()
-            .replace("-edited", "")
-            .replace(".supplemental-metadata", "")
-            .replace(".json", "");
+        let filetype = FileType::from_path(&path, config.extensions())?;
 
-        Self::normalize_duplicates(&mut name);
+        let album = Self::normalize_album(
+            &path
+                .parent()
+                .ok_or(ParseError::FilePathError(
+                    "Asset path must contain an album name".to_string(),
+                ))?
+                .file_name()
+                .ok_or(ParseError::FilePathError(
+                    "Asset path must contain an album name".to_string(),
+                ))?
+                .to_string_lossy(),
+        );
+
+        let name = Self::normalize(
+            &path
+                .file_name()
+                .ok_or(ParseError::FilePathError(
+                    "Asset path must contain a filename".to_string(),
+                ))?
+                .to_string_lossy(),
+        );
+
+        let folder = Folder::from_path(&path, &album);
+        let album = match folder {
+            Folder::Shared => format!("{}{album}", config.shared_prefix()),
+            _ => album,
+        };
 
         Ok(Self {
+            folder,
             album,
             name,
             filetype,
@@ -94,15 +202,14 @@ pub(crate) enum FileType {
     Unknown,
 }
 
-impl TryFrom<&Cow<'_, Path>> for FileType {
-    type Error = ParseError;
-
-    fn try_from(path: &Cow<'_, Path>) -> Result<Self, Self::Error> {
+impl FileType {
+    /// Derives the type of the file from its extension and name
+    pub fn from_path(path: &Path, extensions: &[(String, AssetType)]) -> Result<Self, ParseError> {
         if let Some(ext) = path.extension() {
             let ext = ext.to_string_lossy().to_lowercase();
             if &ext == "json" {
                 Ok(Self::Metadata)
-            } else if Filename::MEDIA_EXTENSIONS.contains(&ext.as_str()) {
+            } else if Filename::is_media(&ext, extensions) {
                 let filename = path
                     .file_name()
                     .ok_or(ParseError::FilePathError(
@@ -125,10 +232,10 @@ impl TryFrom<&Cow<'_, Path>> for FileType {
     }
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::borrow::Cow;
 
     #[test]
     fn normalize_duplicates() {
@@ -164,4 +271,129 @@ mod tests {
         Filename::normalize_duplicates(&mut s);
         assert_eq!(&s, "IMG_20131023_123627(1).jpg");
     }
+
+    #[test]
+    fn normalize_media_and_metadata() {
+        for name in [
+            "IMG_1234(1).jpg",
+            "IMG_1234(1)-edited.jpg",
+            "IMG_1234.jpg(1).json",
+            "IMG_1234.jpg.supplemental-metadata(1).json",
+        ] {
+            assert_eq!(Filename::normalize(name), "IMG_1234(1).jpg");
+        }
+        for name in [
+            "IMG_1234.jpg",
+            "IMG_1234-edited.jpg",
+            "IMG_1234.jpg.json",
+            "IMG_1234.jpg.supplemental-metadata.json",
+        ] {
+            assert_eq!(Filename::normalize(name), "IMG_1234.jpg");
+        }
+    }
+
+    #[test]
+    fn normalize_album() {
+        assert_eq!(Filename::normalize_album("Vacation"), "Vacation");
+        assert_eq!(Filename::normalize_album(" Vacation "), "Vacation");
+        assert_eq!(
+            Filename::normalize_album("Summer \t Vacation\u{a0}"),
+            "Summer Vacation"
+        );
+    }
+
+    #[test]
+    fn edited_name() {
+        assert_eq!(
+            Filename::edited_name("IMG_20131023_123651.jpg"),
+            "IMG_20131023_123651-edited.jpg"
+        );
+        assert_eq!(
+            Filename::edited_name("IMG_20131023_123651(1).jpg"),
+            "IMG_20131023_123651(1)-edited.jpg"
+        );
+        assert_eq!(Filename::edited_name("IMG"), "IMG-edited");
+    }
+
+    #[test]
+    fn special_folders() {
+        assert_eq!(Folder::from("Archive"), Folder::Archive);
+        assert_eq!(Folder::from("Trash"), Folder::Trash);
+        assert_eq!(Folder::from("Holiday"), Folder::Album);
+        assert_eq!(Folder::from("Photos from 2019"), Folder::Album);
+
+        let folder = |path: &str, album: &str| Folder::from_path(Path::new(path), album);
+        assert_eq!(
+            folder("Takeout/Google Photos/Archive/IMG_1.jpg", "Archive"),
+            Folder::Archive
+        );
+        assert_eq!(
+            folder("Takeout/Google Photos/Trash/IMG_1.jpg", "Trash"),
+            Folder::Trash
+        );
+        assert_eq!(
+            folder("Takeout/Google Photos/Holiday/Archive/IMG_1.jpg", "Archive"),
+            Folder::Album
+        );
+        assert_eq!(folder("Trash/IMG_1.jpg", "Trash"), Folder::Album);
+        assert_eq!(
+            folder(
+                "Takeout/Google Photos/Shared albums/Holiday/IMG_1.jpg",
+                "Holiday"
+            ),
+            Folder::Shared
+        );
+        assert_eq!(
+            folder(
+                "Takeout/Google Photos/Shared albums/Trash/IMG_1.jpg",
+                "Trash"
+            ),
+            Folder::Shared
+        );
+        assert_eq!(
+            folder(
+                "Takeout/Google Photos/Shared albums/IMG_1.jpg",
+                "Shared albums"
+            ),
+            Folder::Album
+        );
+        assert_eq!(
+            folder("Takeout/Shared albums/Holiday/IMG_1.jpg", "Holiday"),
+            Folder::Album
+        );
+    }
+
+    #[test]
+    fn heif_filetype() {
+        for name in [
+            "Album/IMG_1.HEIF",
+            "Album/IMG_1.heic",
+            "Album/IMG_1.HEIC",
+            "Album/IMG_1.heif",
+            "Album/IMG_1.avif",
+        ] {
+            let path = Cow::from(Path::new(name));
+            assert!(FileType::from_path(&path, &[]).unwrap() == FileType::Original);
+        }
+        let path = Cow::from(Path::new("Album/IMG_1-edited.HEIF"));
+        assert!(FileType::from_path(&path, &[]).unwrap() == FileType::Edited);
+    }
+
+    #[test]
+    fn video_filetype() {
+        for name in ["Album/VID_1.mp4", "Album/VID_1.MOV", "Album/VID_1.avi"] {
+            let path = Cow::from(Path::new(name));
+            assert!(FileType::from_path(&path, &[]).unwrap() == FileType::Original);
+        }
+    }
+
+    #[test]
+    fn audio_filetype() {
+        for name in ["Album/memo.mp3", "Album/memo.M4A", "Album/memo.flac"] {
+            let path = Cow::from(Path::new(name));
+            assert!(FileType::from_path(&path, &[]).unwrap() == FileType::Original);
+        }
+        let path = Cow::from(Path::new("Album/memo.txt"));
+        assert!(FileType::from_path(&path, &[]).unwrap() == FileType::Unknown);
+    }
 }
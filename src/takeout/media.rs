@@ -1,56 +1,73 @@
-use std::collections::{hash_map::Values, HashMap};
+use std::collections::{hash_map::Values, HashMap, HashSet};
 
 use time::OffsetDateTime;
 
+use crate::takeout::file::Folder;
+use crate::takeout::metadata::Details;
 use crate::takeout::Filename;
 
 pub(crate) struct Media {
     date_taken: Option<OffsetDateTime>,
+    description: Option<String>,
+    favorite: bool,
+    location: Option<(f64, f64)>,
+    people: Vec<String>,
     name: String,
+    edited_name: String,
     edited: bool,
     original: bool,
+    archived: bool,
+    trashed: bool,
     albums: Vec<String>,
 }
 
 impl Media {
-    pub fn from_original(name: String, album: String) -> Self {
+    fn new(name: String) -> Self {
         Self {
             date_taken: None,
+            description: None,
+            favorite: false,
+            location: None,
+            people: Vec::new(),
+            edited_name: Filename::edited_name(&name),
             name,
             edited: false,
-            original: true,
-            albums: vec![album],
+            original: false,
+            archived: false,
+            trashed: false,
+            albums: Vec::new(),
         }
     }
 
-    pub fn from_edited(name: String, album: String) -> Self {
-        Self {
-            date_taken: None,
-            name,
-            edited: true,
-            original: false,
-            albums: vec![album],
-        }
+    pub fn date_taken(&self) -> Option<OffsetDateTime> {
+        self.date_taken
     }
 
-    pub fn from_metadata(name: String, album: String, date_taken: OffsetDateTime) -> Self {
-        Self {
-            date_taken: Some(date_taken),
-            name,
-            edited: false,
-            original: false,
-            albums: vec![album],
-        }
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
     }
 
-    pub fn date_taken(&self) -> Option<OffsetDateTime> {
-        self.date_taken
+    pub fn favorite(&self) -> bool {
+        self.favorite
+    }
+
+    pub fn location(&self) -> Option<(f64, f64)> {
+        self.location
+    }
+
+    pub fn people(&self) -> &[String] {
+        &self.people
     }
 
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// The name of the edited version, used when both versions are uploaded
+    pub fn edited_name(&self) -> &str {
+        &self.edited_name
+    }
+
     pub fn original(&self) -> bool {
         self.original
     }
@@ -59,16 +76,41 @@ impl Media {
         self.edited
     }
 
+    /// Returns true if the media is in the special `Archive` folder
+    pub fn archived(&self) -> bool {
+        self.archived
+    }
+
+    /// Returns true if the media is in the special `Trash` folder
+    pub fn trashed(&self) -> bool {
+        self.trashed
+    }
+
     pub fn albums(&self) -> &[String] {
         &self.albums
     }
 
+    /// Adds the album of the file, or flags the media if it is in a special folder
+    fn add_folder(&mut self, file: &Filename) {
+        match file.folder() {
+            Folder::Album | Folder::Shared => self.add_album(file.album()),
+            Folder::Archive => self.archived = true,
+            Folder::Trash => self.trashed = true,
+        }
+    }
+
     pub fn add_album(&mut self, album: &str) {
-        self.albums.push(album.to_string());
+        if !self.albums.iter().any(|name| name == album) {
+            self.albums.push(album.to_string());
+        }
     }
 
-    pub fn set_date_taken(&mut self, date_taken: OffsetDateTime) {
-        self.date_taken = Some(date_taken)
+    pub fn set_details(&mut self, details: Details) {
+        self.date_taken = Some(details.date_taken);
+        self.description = details.description;
+        self.favorite = details.favorite;
+        self.location = details.location;
+        self.people = details.people;
     }
 
     pub fn add_edited(&mut self) {
@@ -83,47 +125,40 @@ impl Media {
 #[derive(Default)]
 pub(crate) struct MediaStore {
     media: HashMap<String, Media>,
+    shared_albums: HashSet<String>,
 }
 
 impl MediaStore {
-    pub fn add_metadata(&mut self, file: &Filename, date_taken: OffsetDateTime) {
-        self.media
+    fn entry(&mut self, file: &Filename) -> &mut Media {
+        if *file.folder() == Folder::Shared {
+            self.add_shared_album(file);
+        }
+        let media = self
+            .media
             .entry(file.name().to_string())
-            .and_modify(|entry| {
-                entry.set_date_taken(date_taken);
-                entry.add_album(file.album());
-            })
-            .or_insert(Media::from_metadata(
-                file.name().to_string(),
-                file.album().to_string(),
-                date_taken,
-            ));
+            .or_insert_with(|| Media::new(file.name().to_string()));
+        media.add_folder(file);
+        media
+    }
+
+    pub fn add_metadata(&mut self, file: &Filename, details: Details) {
+        self.entry(file).set_details(details);
+    }
+
+    pub fn add_shared_album(&mut self, file: &Filename) {
+        self.shared_albums.insert(file.album().to_string());
+    }
+
+    pub fn shared(&self, album: &str) -> bool {
+        self.shared_albums.contains(album)
     }
 
     pub fn add_original(&mut self, file: &Filename) {
-        self.media
-            .entry(file.name().to_string())
-            .and_modify(|entry| {
-                entry.add_original();
-                entry.add_album(file.album());
-            })
-            .or_insert(Media::from_original(
-                file.name().to_string(),
-                file.album().to_string(),
-            ));
+        self.entry(file).add_original();
     }
 
     pub fn add_edited(&mut self, file: &Filename) {
-        self.media
-            .entry(file.name().to_string())
-            .and_modify(|entry| {
-                entry.add_edited();
-                entry.add_album(file.album());
-            })
-            .or_insert(Media::from_edited(
-                file.name().to_string(),
-                file.album().to_string(),
-            ));
+        self.entry(file).add_edited();
     }
 
     pub fn values(&self) -> Values<'_, String, Media> {
@@ -1,9 +1,6 @@
 use std::fmt::Display;
-use std::fs::File;
 use std::io::Read;
-use std::num::ParseIntError;
 
-use flate2::read::GzDecoder;
 use serde::{Deserialize, Serialize};
 use tar::Entry;
 use time::format_description::BorrowedFormatItem;
@@ -34,38 +31,121 @@ impl Display for PhotoTakenTime {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+struct Person {
+    name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct GeoData {
+    latitude: f64,
+    longitude: f64,
+}
+
 #[allow(non_snake_case)]
 #[derive(Serialize, Deserialize)]
 struct Metadata {
-    photoTakenTime: PhotoTakenTime,
+    photoTakenTime: Option<PhotoTakenTime>,
+    creationTime: Option<PhotoTakenTime>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    favorited: bool,
+    geoData: Option<GeoData>,
+    #[serde(default)]
+    people: Vec<Person>,
+}
+
+/// The metadata of an image or video that is preserved when importing it
+pub(crate) struct Details {
+    pub date_taken: OffsetDateTime,
+    pub description: Option<String>,
+    pub favorite: bool,
+    pub location: Option<(f64, f64)>,
+    pub people: Vec<String>,
 }
 
 impl Metadata {
-    fn timestamp(&self) -> Result<i64, ParseIntError> {
-        self.photoTakenTime.timestamp.parse::<i64>()
+    /// Latitude and longitude, Google uses `0.0` for both if the location is unknown
+    fn location(&self) -> Option<(f64, f64)> {
+        self.geoData
+            .as_ref()
+            .filter(|geo| geo.latitude != 0.0 || geo.longitude != 0.0)
+            .map(|geo| (geo.latitude, geo.longitude))
+    }
+
+    /// The timestamp when the photo was taken, falling back to the creation time
+    fn timestamp(&self) -> ParseResult<i64> {
+        self.photoTakenTime
+            .as_ref()
+            .or(self.creationTime.as_ref())
+            .ok_or_else(|| {
+                ParseError::InvalidMetadata(
+                    "Neither photoTakenTime nor creationTime is present".to_string(),
+                )
+            })?
+            .timestamp
+            .parse::<i64>()
+            .map_err(|_| ParseError::InvalidMetadata("Can't parse Timestamp to i64".to_string()))
+    }
+}
+
+/// The metadata of an album, stored in a `metadata.json` file in the album folder
+#[derive(Deserialize)]
+struct AlbumMetadata {
+    /// Albums that were shared via link have an `access` level
+    #[serde(default)]
+    access: String,
+    #[serde(default)]
+    shared: bool,
+}
+
+impl AlbumMetadata {
+    fn shared(&self) -> bool {
+        self.shared || !self.access.is_empty()
     }
 }
 
-pub(crate) fn parse(entry: &mut Entry<'_, GzDecoder<File>>) -> ParseResult<OffsetDateTime> {
+fn read_json<T: for<'de> Deserialize<'de>, R: Read>(entry: &mut Entry<'_, R>) -> ParseResult<T> {
     let mut json = String::with_capacity(entry.size().try_into().unwrap());
-    let _ = entry.read_to_string(&mut json)?;
+    let _ = entry
+        .read_to_string(&mut json)
+        .map_err(|err| match entry.path() {
+            Ok(path) => ParseError::IoAt {
+                path: path.display().to_string(),
+                source: err,
+            },
+            Err(_) => ParseError::Io { source: err },
+        })?;
 
-    let meta: Metadata = serde_json::from_str(&json).map_err(|_| {
+    serde_json::from_str(&json).map_err(|_| {
         ParseError::InvalidMetadata(format!(
             "Can't parse JSON for {}",
             entry.path().expect("Metadata comes with a path").display()
         ))
-    })?;
+    })
+}
 
-    let date_taken =
-        OffsetDateTime::from_unix_timestamp(meta.timestamp().map_err(|_| {
-            ParseError::InvalidMetadata("Can't parse Timestamp to i64".to_string())
-        })?)
-        .map_err(|_| {
-            ParseError::InvalidMetadata("Can't create OffsetData from timestamp".to_string())
-        })?;
+/// Returns true if the album metadata marks the album as shared
+pub(crate) fn parse_album<R: Read>(entry: &mut Entry<'_, R>) -> ParseResult<bool> {
+    let meta: AlbumMetadata = read_json(entry)?;
+    Ok(meta.shared())
+}
+
+pub(crate) fn parse<R: Read>(entry: &mut Entry<'_, R>) -> ParseResult<Details> {
+    let meta: Metadata = read_json(entry)?;
 
-    Ok(date_taken)
+    let date_taken = OffsetDateTime::from_unix_timestamp(meta.timestamp()?).map_err(|_| {
+        ParseError::InvalidMetadata("Can't create OffsetData from timestamp".to_string())
+    })?;
+
+    Ok(Details {
+        date_taken,
+        location: meta.location(),
+        favorite: meta.favorited,
+        description: Some(meta.description).filter(|description| !description.is_empty()),
+        people: meta.people.into_iter().map(|person| person.name).collect(),
+    })
 }
 
 #[cfg(test)]
@@ -90,6 +170,79 @@ mod tests {
 "#;
         let p: Metadata = serde_json::from_str(data).unwrap();
 
-        assert_eq!(p.photoTakenTime.timestamp, String::from("1370762069"));
+        assert_eq!(p.timestamp().unwrap(), 1370762069);
+        assert!(p.description.is_empty());
+        assert!(!p.favorited);
+        assert!(p.location().is_none());
+        assert!(p.people.is_empty());
+    }
+
+    #[test]
+    fn favorite_with_location() {
+        let data = r#"
+{
+  "title": "IMG_20130609_101429.jpg",
+  "description": "Sunset at the beach",
+  "photoTakenTime": {
+    "timestamp": "1370762069",
+    "formatted": "Jun 9, 2013, 7:14:29 AM UTC"
+  },
+  "geoData": {
+    "latitude": 52.5186,
+    "longitude": -13.4081,
+    "altitude": 34.0,
+    "latitudeSpan": 0.0,
+    "longitudeSpan": 0.0
+  },
+  "people": [{"name": "Alice"}, {"name": "Bob"}],
+  "favorited": true
+}
+"#;
+        let p: Metadata = serde_json::from_str(data).unwrap();
+
+        assert_eq!(p.description, "Sunset at the beach");
+        assert!(p.favorited);
+        assert_eq!(p.location(), Some((52.5186, -13.4081)));
+        let people: Vec<&str> = p.people.iter().map(|person| person.name.as_str()).collect();
+        assert_eq!(people, ["Alice", "Bob"]);
+    }
+
+    #[test]
+    fn creation_time_fallback() {
+        let data = r#"
+{
+  "title": "IMG_20130609_101429.jpg",
+  "description": "",
+  "creationTime": {
+    "timestamp": "1400220491",
+    "formatted": "May 16, 2014, 6:08:11 AM UTC"
+  }
+}
+"#;
+        let p: Metadata = serde_json::from_str(data).unwrap();
+        assert_eq!(p.timestamp().unwrap(), 1400220491);
+
+        let p: Metadata = serde_json::from_str(r#"{"title": "IMG.jpg"}"#).unwrap();
+        assert!(p.timestamp().is_err());
+    }
+
+    #[test]
+    fn shared_album() {
+        let data = r#"
+{
+  "title": "Holiday",
+  "description": "",
+  "access": "protected",
+  "date": {
+    "timestamp": "1400220491",
+    "formatted": "May 16, 2014, 6:08:11 AM UTC"
+  }
+}
+"#;
+        let album: AlbumMetadata = serde_json::from_str(data).unwrap();
+        assert!(album.shared());
+
+        let album: AlbumMetadata = serde_json::from_str(r#"{"title": "Wedding"}"#).unwrap();
+        assert!(!album.shared());
     }
 }
@@ -1,17 +1,45 @@
-use crossbeam_channel::{unbounded, Sender};
+use crossbeam_channel::{bounded, unbounded, Sender};
+use log::warn;
+use time::UtcOffset;
 
 use crate::{
-    api::requests::MovedAsset,
+    api::{bulk_check::SkipPresent, requests::MovedAsset},
     takeout::Record,
     upload::{Status, Uploaded},
-    utils::Id,
-    Album, AssetId, Client, ImmichError, ImmichResult,
+    Album, AssetId, AssetType, Client, GetOrCreate, ImmichError, ImmichResult,
+};
+use std::{
+    cell::Cell,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs::File,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    vec::IntoIter,
 };
-use std::{collections::HashMap, fs::File, path::Path, thread};
 
 use crate::Asset;
 
-use super::{ParseResult, Takeout};
+use super::{HandleEdited, ParseResult, ScanProgress, Takeout, TakeoutConfig};
+
+/// Description of newly created albums that were shared in Google Photos
+const SHARED_ALBUM_DESCRIPTION: &str = "Shared album in Google Photos";
+
+/// Defines how assets that already exist on the Immich server are added to albums
+///
+/// See [`Uploader::duplicate_album_policy`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateAlbumPolicy {
+    /// Existing assets are added to the albums of the Takeout archive, like new assets
+    #[default]
+    AddToTakeoutAlbums,
+    /// Existing assets are not added to the albums of the Takeout archive
+    ///
+    /// They are still reported as successfully moved, with an
+    /// [`AssetMoveError::Duplicate`](crate::AssetMoveError::Duplicate) error to tell them apart.
+    LeaveAsIs,
+}
 
 /// Prepare a Google Takeout archive for uploading to Immich
 ///
@@ -48,11 +76,18 @@ use super::{ParseResult, Takeout};
 ///     .unwrap();
 ///
 /// for asset in res {
-///     println!("{}", asset.id())
+///     println!("{}", asset.id().map(ToString::to_string).unwrap_or_default())
 /// }
 /// ```
 pub struct Uploader {
     takeout: Takeout,
+    source: String,
+    skip_existing: bool,
+    skip_trashed: bool,
+    stack_edited: bool,
+    utc_offset: Option<UtcOffset>,
+    album_progress: Option<Sender<AlbumProgress>>,
+    duplicate_albums: DuplicateAlbumPolicy,
 }
 
 impl Uploader {
@@ -76,9 +111,291 @@ impl Uploader {
     /// println!("The takeout archive contains {} images and videos", takeout.len());
     /// ```
     pub fn new<P: AsRef<Path>>(path: P) -> ParseResult<Self> {
+        Self::with_config(path, TakeoutConfig::default())
+    }
+
+    /// Crate a new `Uploader` with a custom configuration
+    ///
+    /// Works like [`Uploader::new`], but parses the archive with the rules of the `config`
+    /// and adds all assets to its import album (see [`TakeoutConfig`] for details).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::takeout::{TakeoutConfig, Uploader};
+    ///
+    /// let config = TakeoutConfig::default().import_album("Google Photos 2024");
+    /// let takeout = Uploader::with_config("/path/to/takeout/file.tar.gz", config).unwrap();
+    ///
+    /// println!("The takeout archive contains {} images and videos", takeout.len());
+    /// ```
+    pub fn with_config<P: AsRef<Path>>(path: P, config: TakeoutConfig) -> ParseResult<Self> {
+        let source = path
+            .as_ref()
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
         let file = File::open(path)?;
-        let takeout = Takeout::new(file)?;
-        Ok(Self { takeout })
+        let takeout = Takeout::with_config(file, config)?;
+        Ok(Self {
+            takeout,
+            source,
+            skip_existing: false,
+            skip_trashed: false,
+            stack_edited: false,
+            utc_offset: None,
+            album_progress: None,
+            duplicate_albums: DuplicateAlbumPolicy::default(),
+        })
+    }
+
+    /// Crate a new `Uploader` for a Google Takeout archive that is split into multiple parts
+    ///
+    /// Google Takeout splits large exports into several archives, e.g. `takeout-001.tgz`,
+    /// `takeout-002.tgz`. All parts are scanned together, so that metadata and albums are
+    /// correctly assigned, even if they are in a different part than the image or video.
+    /// See [`Takeout::from_parts`] for details.
+    ///
+    /// # Errors
+    ///
+    /// The method might fail if any of the Takeout archives cannot be read due to IO errors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    /// use immich::takeout::Uploader;
+    ///
+    /// let takeout = Uploader::from_parts(&[
+    ///     PathBuf::from("/path/to/takeout/takeout-001.tgz"),
+    ///     PathBuf::from("/path/to/takeout/takeout-002.tgz"),
+    /// ]).unwrap();
+    ///
+    /// println!("The takeout archive contains {} images and videos", takeout.len());
+    /// ```
+    pub fn from_parts(paths: &[PathBuf]) -> ParseResult<Self> {
+        Self::with_progress(paths, |_| {})
+    }
+
+    /// Crate a new `Uploader` and report the progress of the initial scan of the archive
+    ///
+    /// Works like [`Uploader::from_parts`], but calls `progress` after every scanned file of
+    /// the archive, see [`Takeout::with_progress`].
+    ///
+    /// # Errors
+    ///
+    /// The method might fail if any of the Takeout archives cannot be read due to IO errors.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::path::PathBuf;
+    /// use immich::takeout::Uploader;
+    ///
+    /// let takeout = Uploader::with_progress(
+    ///     &[PathBuf::from("/path/to/takeout/file.tar.gz")],
+    ///     |progress| print!("\rScanning archive: {} files indexed", progress.entries()),
+    /// )
+    /// .unwrap();
+    ///
+    /// println!("\nThe takeout archive contains {} images and videos", takeout.len());
+    /// ```
+    pub fn with_progress<F: FnMut(&ScanProgress)>(
+        paths: &[PathBuf],
+        progress: F,
+    ) -> ParseResult<Self> {
+        let source = paths
+            .iter()
+            .filter_map(|path| path.file_name())
+            .map(|name| name.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let files = paths
+            .iter()
+            .map(File::open)
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let takeout = Takeout::with_progress(files, HandleEdited::PreferEdited, progress)?;
+        Ok(Self {
+            takeout,
+            source,
+            skip_existing: false,
+            skip_trashed: false,
+            stack_edited: false,
+            utc_offset: None,
+            album_progress: None,
+            duplicate_albums: DuplicateAlbumPolicy::default(),
+        })
+    }
+
+    /// Skip uploading assets that are already present on the Immich server
+    ///
+    /// If enabled, [`Uploader::upload`] checks the assets in batches with the server before
+    /// uploading them and only uploads the missing ones. This speeds up re-running a partially
+    /// completed import considerably. Skipped assets are reported as
+    /// [`Status::Duplicate`] and are still added to their albums.
+    ///
+    /// This option is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::takeout::Uploader;
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    /// takeout.skip_existing(true);
+    /// ```
+    pub fn skip_existing(&mut self, skip: bool) -> &mut Self {
+        self.skip_existing = skip;
+        self
+    }
+
+    /// Sets how assets that already exist on the Immich server are added to albums
+    ///
+    /// By default, existing assets (reported as [`Status::Duplicate`]) are added to the albums
+    /// of the Takeout archive, just like newly uploaded ones (see
+    /// [`DuplicateAlbumPolicy::AddToTakeoutAlbums`]). If the library on the server is organized
+    /// already, use [`DuplicateAlbumPolicy::LeaveAsIs`] to only add the new assets to the albums.
+    /// All assets are still added to the import album.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::takeout::{DuplicateAlbumPolicy, Uploader};
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    /// takeout
+    ///     .skip_existing(true)
+    ///     .duplicate_album_policy(DuplicateAlbumPolicy::LeaveAsIs);
+    /// ```
+    pub fn duplicate_album_policy(&mut self, policy: DuplicateAlbumPolicy) -> &mut Self {
+        self.duplicate_albums = policy;
+        self
+    }
+
+    /// Skip uploading images and videos from the `Trash` folder of the Takeout archive
+    ///
+    /// By default, images and videos from the `Trash` folder are uploaded and then moved to
+    /// the trash of the Immich server. Images and videos from the `Archive` folder are
+    /// always uploaded and archived on the Immich server.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::takeout::Uploader;
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    /// takeout.skip_trashed(true);
+    /// ```
+    pub fn skip_trashed(&mut self, skip: bool) -> &mut Self {
+        self.skip_trashed = skip;
+        self
+    }
+
+    /// Uploads the original version of edited images and videos, too
+    ///
+    /// By default, only the edited version is uploaded (see [`HandleEdited::PreferEdited`]).
+    /// With this option, both versions are uploaded (see [`HandleEdited::UseBoth`]) and the
+    /// edited version is named `<name>-edited.<ext>`. Use [`Uploader::stack_edited`] to group
+    /// both versions into a stack.
+    ///
+    /// Archives scanned with [`HandleEdited::PreferOriginal`] don't know their edited files,
+    /// so this option is ignored for them and only the originals are uploaded.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::takeout::Uploader;
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    /// takeout.keep_originals();
+    /// ```
+    pub fn keep_originals(&mut self) -> &mut Self {
+        if self.takeout.config().handle_edited() == &HandleEdited::PreferOriginal {
+            warn!("The archive was scanned without edited files, only the originals are uploaded");
+        } else {
+            self.takeout.set_edited_files(HandleEdited::UseBoth);
+        }
+        self
+    }
+
+    /// Groups the original and the edited version of an asset into a stack
+    ///
+    /// The edited version is the primary asset of the stack. This only has an effect
+    /// together with [`Uploader::keep_originals`], otherwise the originals are not uploaded.
+    ///
+    /// This option is disabled by default.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::takeout::Uploader;
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    /// takeout.keep_originals().stack_edited(true);
+    /// ```
+    pub fn stack_edited(&mut self, stack: bool) -> &mut Self {
+        self.stack_edited = stack;
+        self
+    }
+
+    /// Uses the local time of a fixed UTC offset for the timestamps of all assets
+    ///
+    /// Google Takeout stores timestamps in UTC, so Immich shows them shifted by the time zone
+    /// the photos were taken in. Set the offset of that time zone to show the local time instead.
+    /// The sign of `hours` also applies to `minutes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidDate`] if the offset is invalid
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::takeout::Uploader;
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    /// // Central European Time
+    /// takeout.utc_offset(1, 0).unwrap();
+    /// ```
+    pub fn utc_offset(&mut self, hours: i8, minutes: i8) -> ImmichResult<&mut Self> {
+        let offset =
+            UtcOffset::from_hms(hours, minutes, 0).map_err(|_| ImmichError::InvalidDate)?;
+        self.utc_offset = Some(offset);
+        Ok(self)
+    }
+
+    /// Reports the progress of recreating the albums after all assets are uploaded
+    ///
+    /// Once the upload is complete, the uploaded assets are added to the albums they were in
+    /// at Google Photos. For archives with many albums, this takes a while. An [`AlbumProgress`]
+    /// is sent to `progress_channel` after every processed album.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use std::thread;
+    /// use immich::takeout::{AlbumProgress, Uploader};
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (album_sender, album_receiver) = unbounded::<AlbumProgress>();
+    /// thread::spawn(move || {
+    ///     for progress in album_receiver {
+    ///         println!(
+    ///             "[{}/{}] {}: {} assets moved",
+    ///             progress.done(),
+    ///             progress.total(),
+    ///             progress.album(),
+    ///             progress.moved()
+    ///         );
+    ///     }
+    /// });
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    /// takeout.album_progress(album_sender);
+    /// ```
+    pub fn album_progress(&mut self, progress_channel: Sender<AlbumProgress>) -> &mut Self {
+        self.album_progress = Some(progress_channel);
+        self
     }
 
     /// Converts all images and videos of the Google Takeout archive to Immich [`Asset`]s
@@ -105,9 +422,13 @@ impl Uploader {
     /// }
     /// ```
     pub fn assets(&mut self) -> ParseResult<impl Iterator<Item = Asset> + use<'_>> {
+        let utc_offset = self.utc_offset;
         Ok(self.takeout.records()?.filter_map(move |record| {
             if let Ok(record) = record {
-                if let Ok(asset) = Asset::try_from(record) {
+                if let Ok(mut asset) = Asset::try_from(record) {
+                    if let Some(offset) = utc_offset {
+                        asset.apply_offset(offset);
+                    }
                     return Some(asset);
                 }
             }
@@ -172,10 +493,15 @@ impl Uploader {
         &mut self,
         mut filter: F,
     ) -> ParseResult<impl Iterator<Item = Asset> + use<'_, F>> {
+        let utc_offset = self.utc_offset;
         Ok(self.takeout.records()?.filter_map(move |record| {
             if let Ok(record) = record {
                 if filter(&record) {
-                    Some(Asset::try_from(record).unwrap())
+                    let mut asset = Asset::try_from(record).ok()?;
+                    if let Some(offset) = utc_offset {
+                        asset.apply_offset(offset);
+                    }
+                    Some(asset)
                 } else {
                     None
                 }
@@ -188,16 +514,28 @@ impl Uploader {
     /// Upload all images and videos from the Takeout archive to Immich
     ///
     /// All assets are moved to same albums as they were in in Google Photos.
-    /// In addition a new album "Google Takout Import" is created for all assets.
+    /// In addition a new album "Google Takout Import" is created for all assets, see
+    /// [`TakeoutConfig::import_album`] to change its name.
+    /// Images and videos from the special `Archive` folder are archived and those from the
+    /// `Trash` folder are moved to the trash (see [`Uploader::skip_trashed`]).
+    /// Newly created albums that were shared in Google Photos get the description
+    /// "Shared album in Google Photos" (see [`TakeoutAlbums::is_shared`](super::TakeoutAlbums::is_shared)).
+    ///
+    /// The returned [`Imported`] reports the moved assets and which albums were newly
+    /// created or already existed.
     ///
     /// # Errors
     ///
     /// This method has many different ways to fail:
     ///
     /// - The tar archive can't be read: Returns Error
+    /// - The tar archive does not contain any images or videos:
+    ///   Returns [`ImmichError::EmptyTakeoutArchive`], no album is created
     /// - The image/video data can't be extracted from the tar archive:
-    ///   silently ignored and the image/video is skipped
+    ///   the image/video is skipped and counted in [`ImportSummary::unreadable`]
     /// - The "Google Takeout Import album" can't be created: Fails right away
+    /// - The server does not support the file type of an image/video:
+    ///   The image/video is skipped without reading it, reported as failed [`MovedAsset`]
     /// - Some images/videos can't be uploaded to to network, server, etc failure:
     ///   Ignored and the image/video is skipped, reported as failed [`MovedAsset`]
     ///
@@ -234,7 +572,7 @@ impl Uploader {
     ///     .unwrap();
     ///
     /// for asset in res {
-    ///     println!("{}", asset.id())
+    ///     println!("{}", asset.id().map(ToString::to_string).unwrap_or_default())
     /// }
     /// ```
     pub fn upload<F: FnMut(&Record<'_>) -> bool>(
@@ -243,10 +581,234 @@ impl Uploader {
         upload_concurrency: usize,
         progress_channel: Sender<Uploaded>,
         filter_records: F,
-    ) -> ImmichResult<Vec<MovedAsset>> {
-        let assets = self.filter_assets(filter_records)?;
+    ) -> ImmichResult<Imported> {
+        self.upload_filtered(
+            client,
+            upload_concurrency,
+            progress_channel,
+            filter_records,
+            None,
+        )
+    }
+
+    /// Upload only the images and videos that were edited in Google Photos
+    ///
+    /// This is a shortcut for [`Uploader::upload`] with a filter on [`Record::edited`].
+    /// Use [`Takeout::edited_assets`] to list the affected images and videos beforehand.
+    ///
+    /// # Errors
+    ///
+    /// See [`Uploader::upload`]
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    /// use immich::takeout::Uploader;
+    /// use immich::upload::Uploaded;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let (result_sender, result_receiver) = unbounded::<Uploaded>();
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    ///
+    /// let res = takeout
+    ///     .upload_edited_only(&client, 5, result_sender)
+    ///     .unwrap();
+    ///
+    /// println!("Moved {} edited assets", res.len());
+    /// ```
+    pub fn upload_edited_only(
+        &mut self,
+        client: &Client,
+        upload_concurrency: usize,
+        progress_channel: Sender<Uploaded>,
+    ) -> ImmichResult<Imported> {
+        self.upload(client, upload_concurrency, progress_channel, |record| {
+            record.edited()
+        })
+    }
+
+    /// Upload only the images and videos of the given [`AssetType`]s
+    ///
+    /// This is a shortcut for [`Uploader::upload`] with a filter on [`Record::asset_type`].
+    /// The skipped records are not read from the archive. Upload them in a later run,
+    /// they are added to the albums that were created by the first run.
+    ///
+    /// # Errors
+    ///
+    /// See [`Uploader::upload`]
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::{AssetType, Client};
+    /// use immich::takeout::Uploader;
+    /// use immich::upload::Uploaded;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let (result_sender, result_receiver) = unbounded::<Uploaded>();
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    ///
+    /// // upload the photos first, the videos can follow later
+    /// let res = takeout
+    ///     .upload_types(&client, 5, result_sender, &[AssetType::Image])
+    ///     .unwrap();
+    ///
+    /// println!("Moved {} images", res.len());
+    /// ```
+    pub fn upload_types(
+        &mut self,
+        client: &Client,
+        upload_concurrency: usize,
+        progress_channel: Sender<Uploaded>,
+        types: &[AssetType],
+    ) -> ImmichResult<Imported> {
+        self.upload(client, upload_concurrency, progress_channel, |record| {
+            types.contains(&record.asset_type())
+        })
+    }
+
+    /// Upload only the images, but no videos
+    ///
+    /// This is a shortcut for [`Uploader::upload_types`] with [`AssetType::Image`].
+    ///
+    /// # Errors
+    ///
+    /// See [`Uploader::upload`]
+    pub fn upload_images_only(
+        &mut self,
+        client: &Client,
+        upload_concurrency: usize,
+        progress_channel: Sender<Uploaded>,
+    ) -> ImmichResult<Imported> {
+        self.upload_types(
+            client,
+            upload_concurrency,
+            progress_channel,
+            &[AssetType::Image],
+        )
+    }
+
+    /// Upload only the images and videos of the specified albums to Immich
+    ///
+    /// Only the albums in `album_names` are recreated on the Immich server. If an asset is
+    /// in both a requested and another album, it is uploaded, but only added to the
+    /// requested album(s).
+    ///
+    /// In addition, all uploaded assets are added to the "Google Takout Import" album.
+    ///
+    /// # Errors
+    ///
+    /// See [`Uploader::upload`]
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    /// use immich::takeout::Uploader;
+    /// use immich::upload::Uploaded;
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let (result_sender, result_receiver) = unbounded::<Uploaded>();
+    ///
+    /// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+    ///
+    /// let res = takeout
+    ///     .upload_albums(&client, 5, result_sender, &["Holiday 2019", "Wedding"])
+    ///     .unwrap();
+    ///
+    /// println!("Moved {} assets", res.len());
+    /// ```
+    pub fn upload_albums(
+        &mut self,
+        client: &Client,
+        upload_concurrency: usize,
+        progress_channel: Sender<Uploaded>,
+        album_names: &[&str],
+    ) -> ImmichResult<Imported> {
+        self.upload_filtered(
+            client,
+            upload_concurrency,
+            progress_channel,
+            |record| {
+                record
+                    .albums()
+                    .iter()
+                    .any(|album| album_names.contains(&album.as_str()))
+            },
+            Some(album_names),
+        )
+    }
+
+    fn upload_filtered<F: FnMut(&Record<'_>) -> bool>(
+        &mut self,
+        client: &Client,
+        upload_concurrency: usize,
+        progress_channel: Sender<Uploaded>,
+        mut filter_records: F,
+        album_names: Option<&[&str]>,
+    ) -> ImmichResult<Imported> {
+        if self.is_empty() {
+            return Err(ImmichError::EmptyTakeoutArchive);
+        }
+        let skip_existing = self.skip_existing;
+        let skip_trashed = self.skip_trashed;
+        let description = format!("Imported from Google Takeout archive {}", self.source);
+        let import_album = self.takeout.config().import_album_name().to_string();
+        // all albums are checked, so it's worth fetching the album list only once
+        let client = &client.clone().with_album_cache();
+        let media_types = client
+            .supported_media_types()
+            .map_err(|err| warn!("Cannot read the supported media types of the server: {err}"))
+            .ok();
 
         let (proxy_sender, proxy_receiver) = unbounded::<Uploaded>();
+        let unsupported = proxy_sender.clone();
+        // a photo is in the archive once per folder, so all counts are per record
+        let scanned = &Cell::new(0);
+        let excluded = &Cell::new(0);
+
+        let assets = self.filter_assets(move |record| {
+            scanned.set(scanned.get() + 1);
+            if (skip_trashed && record.trashed()) || !filter_records(record) {
+                excluded.set(excluded.get() + 1);
+                return false;
+            }
+            match &media_types {
+                Some(media_types) if !media_types.supports(record.name()) => {
+                    warn!(
+                        "Skipping {}, the server does not support its file type",
+                        record.name()
+                    );
+                    let _ = unsupported.send(Uploaded::from_failure(
+                        record.name(),
+                        &"The server does not support the file type",
+                    ));
+                    false
+                }
+                _ => true,
+            }
+        })?;
 
         let threads = thread::spawn(move || {
             let mut thread_results: Vec<Uploaded> = Vec::new();
@@ -259,63 +821,520 @@ impl Uploader {
             thread_results
         });
 
-        let album = Album::get_or_create(client, "Google Takout Import".to_string())?;
-        client.upload_to_album(upload_concurrency, assets, &album, Some(proxy_sender))?;
+        let album = Album::get_or_create(client, import_album)?;
+        if album.created() {
+            let _ = album.album().set_description(client, &description);
+        }
+        if skip_existing {
+            // the checked assets wait in the batch, so it takes the place of the upload buffer
+            let batch_size = client.upload_buffer().unwrap_or(upload_concurrency * 2);
+            let assets = SkipPresent::new(client, assets, batch_size, proxy_sender.clone());
+            client.clone().with_upload_buffer(0).upload(
+                upload_concurrency,
+                assets,
+                Some(proxy_sender),
+            )?;
+        } else {
+            client.upload(upload_concurrency, assets, Some(proxy_sender))?;
+        }
 
         let uploaded = threads.join().map_err(|_| ImmichError::Multithread)?;
+        // skipped assets are added to the import album as well
+        album.album().add_uploaded(client, &uploaded)?;
+        let summary = ImportSummary::new(scanned.get(), scanned.get() - excluded.get(), &uploaded);
+        if summary.unreadable() > 0 {
+            warn!(
+                "{} of {} images and videos could not be read from the archive",
+                summary.unreadable(),
+                summary.attempted()
+            );
+        }
+        self.archive_and_trash(client, &uploaded);
+        if self.stack_edited {
+            self.stack_edited_versions(client, &uploaded);
+        }
 
-        Ok(self.recreate_albums(client, uploaded))
+        let mut imported =
+            self.recreate_albums(client, upload_concurrency, uploaded, album_names)?;
+        imported.albums.insert(0, album);
+        imported.summary = summary;
+        Ok(imported)
     }
 
-    /// Move the uploaded assets to the same albums they were in at Google Photos
-    fn recreate_albums(&self, client: &Client, uploaded: Vec<Uploaded>) -> Vec<MovedAsset> {
-        /// Helper function to add assets that failed to be moved to an album to the result data
-        fn device_ids_to_moved_asset_failure(
-            asset_device_ids: &[&str],
-            filename2assetid: &mut HashMap<&str, &Id>,
-            moved_assets: &mut Vec<MovedAsset>,
-        ) {
-            moved_assets.extend(asset_device_ids.iter().filter_map(|id| {
-                filename2assetid
-                    .get(id)
-                    .map(|&id| MovedAsset::new(id.clone(), false))
-            }))
+    /// Archives and trashes the newly created assets from the special `Archive` and `Trash` folders
+    ///
+    /// Failures are only logged, the assets remain uploaded.
+    fn archive_and_trash(&self, client: &Client, uploaded: &[Uploaded]) {
+        let mut archived: Vec<AssetId> = Vec::new();
+        let mut trashed: Vec<AssetId> = Vec::new();
+        for asset in uploaded
+            .iter()
+            .filter(|asset| asset.status() == &Status::Created)
+        {
+            if let Some(media) = self.takeout.media(asset.device_asset_id()) {
+                if media.archived() {
+                    archived.extend(asset.id().cloned());
+                }
+                if media.trashed() {
+                    trashed.extend(asset.id().cloned());
+                }
+            }
+        }
+        if let Err(err) = client.set_archived(&archived, true) {
+            warn!("Failed to archive {} assets: {err}", archived.len());
         }
+        if let Err(err) = client.trash_assets(&trashed) {
+            warn!("Failed to trash {} assets: {err}", trashed.len());
+        }
+    }
 
-        // A lookup between the local (Takeout) filename and the Immich Asset Id
-        let mut filename2assetid: HashMap<&str, &AssetId> = HashMap::new();
+    /// Stacks the edited and the original version of the uploaded assets
+    ///
+    /// Failures are only logged, the assets remain uploaded.
+    fn stack_edited_versions(&self, client: &Client, uploaded: &[Uploaded]) {
+        let ids: HashMap<&str, &AssetId> = uploaded
+            .iter()
+            .filter_map(|asset| asset.id().map(|id| (asset.device_asset_id(), id)))
+            .collect();
+        for (edited, original) in self.takeout.edited_pairs() {
+            if let (Some(&edited_id), Some(&original_id)) = (ids.get(edited), ids.get(original)) {
+                if let Err(err) = client.create_stack(&[edited_id.clone(), original_id.clone()]) {
+                    warn!("Failed to stack {edited} with {original}: {err}");
+                }
+            }
+        }
+    }
+
+    /// Move the uploaded assets to the same albums they were in at Google Photos
+    ///
+    /// The albums are processed in parallel, using `concurrency` threads.
+    ///
+    /// If `album_names` is specified, only these albums are recreated
+    fn recreate_albums(
+        &self,
+        client: &Client,
+        concurrency: usize,
+        uploaded: Vec<Uploaded>,
+        album_names: Option<&[&str]>,
+    ) -> ImmichResult<Imported> {
+        let add_duplicates = self.duplicate_albums == DuplicateAlbumPolicy::AddToTakeoutAlbums;
+        // A lookup between the local (Takeout) filename and the upload result
+        let mut filename2uploaded: HashMap<&str, &Uploaded> = HashMap::new();
         for asset in &uploaded {
-            if asset.status() == &Status::Created || asset.status() == &Status::Duplicate {
-                filename2assetid.insert(asset.device_asset_id(), asset.id());
+            if matches!(asset.status(), Status::Created | Status::Duplicate) {
+                filename2uploaded.insert(asset.device_asset_id(), asset);
             }
         }
+        let filename2uploaded = &filename2uploaded;
+
+        let albums = self.takeout.albums();
+        let selected: Vec<(&str, &Vec<&str>)> = (&albums)
+            .into_iter()
+            .filter(|&(album_name, _)| album_names.is_none_or(|names| names.contains(album_name)))
+            .map(|(&album_name, asset_device_ids)| (album_name, asset_device_ids))
+            .collect();
+        let total = selected.len();
+        let done = &AtomicUsize::new(0);
+        let progress = self.album_progress.as_ref();
+
+        let (album_sender, album_receiver) = bounded::<(&str, bool, Vec<&str>)>(concurrency * 2);
+        let (result_sender, result_receiver) =
+            unbounded::<(Option<GetOrCreate>, Vec<MovedAsset>)>();
+
+        thread::scope(|scope| {
+            for _ in 0..concurrency.max(1) {
+                let rec = album_receiver.clone();
+                let res = result_sender.clone();
+                scope.spawn(move || {
+                    while let Ok((album_name, shared, asset_device_ids)) = rec.recv() {
+                        let result = Self::recreate_album(
+                            client,
+                            album_name,
+                            shared,
+                            &asset_device_ids,
+                            filename2uploaded,
+                            add_duplicates,
+                        );
+                        if let Some(progress) = progress {
+                            let _ = progress.send(AlbumProgress {
+                                album: album_name.to_string(),
+                                moved: result.1.iter().filter(|moved| moved.success()).count(),
+                                failed: result.1.iter().filter(|moved| !moved.success()).count(),
+                                done: done.fetch_add(1, Ordering::SeqCst) + 1,
+                                total,
+                            });
+                        }
+                        let _ = res.send(result);
+                    }
+                });
+            }
+            drop(result_sender);
+
+            for (album_name, asset_device_ids) in selected {
+                let shared = albums.is_shared(album_name);
+                album_sender.send((album_name, shared, asset_device_ids.clone()))?;
+            }
+            drop(album_sender);
+            Ok::<(), ImmichError>(())
+        })?;
 
         let mut moved_assets: Vec<MovedAsset> = Vec::new();
-        for (album_name, asset_device_ids) in self.takeout.albums() {
-            if let Ok(album) = Album::get_or_create(client, album_name.to_string()) {
-                // Iterate Immich Asset IDs of all uploaded assets
-                let assets = asset_device_ids
-                    .iter()
-                    .filter_map(|id| filename2assetid.get(id).map(|&id| id.clone()));
+        let mut albums: Vec<GetOrCreate> = Vec::new();
+        for (album, mut result) in result_receiver {
+            albums.extend(album);
+            moved_assets.append(&mut result);
+        }
+        Ok(Imported {
+            assets: moved_assets,
+            albums,
+            summary: ImportSummary::default(),
+        })
+    }
 
-                if let Ok(mut result) = album.add_assets(client, assets) {
-                    moved_assets.append(&mut result);
-                } else {
-                    device_ids_to_moved_asset_failure(
-                        &asset_device_ids,
-                        &mut filename2assetid,
-                        &mut moved_assets,
-                    );
+    /// Move the uploaded assets to a single album
+    ///
+    /// Newly created albums that were `shared` in Google Photos get a description to mark them.
+    /// Unless `add_duplicates` is set, assets that existed on the server already are not
+    /// added and reported as [`MovedAsset::from_duplicate`].
+    fn recreate_album(
+        client: &Client,
+        album_name: &str,
+        shared: bool,
+        asset_device_ids: &[&str],
+        filename2uploaded: &HashMap<&str, &Uploaded>,
+        add_duplicates: bool,
+    ) -> (Option<GetOrCreate>, Vec<MovedAsset>) {
+        /// Helper function to report assets that failed to be moved to the album
+        fn device_ids_to_moved_asset_failure(
+            asset_device_ids: &[&str],
+            filename2uploaded: &HashMap<&str, &Uploaded>,
+        ) -> Vec<MovedAsset> {
+            asset_device_ids
+                .iter()
+                .filter_map(|id| filename2uploaded.get(id))
+                .filter_map(|&uploaded| {
+                    uploaded.id().map(|id| {
+                        let mut moved = MovedAsset::new(id.clone(), false);
+                        moved.set_upload_status(uploaded.status().clone());
+                        moved
+                    })
+                })
+                .collect()
+        }
+
+        let (asset_device_ids, left): (Vec<&str>, Vec<&str>) =
+            asset_device_ids.iter().partition(|&id| {
+                add_duplicates
+                    || filename2uploaded
+                        .get(id)
+                        .is_none_or(|uploaded| uploaded.status() != &Status::Duplicate)
+            });
+        let asset_device_ids = &asset_device_ids[..];
+        let left = left
+            .into_iter()
+            .filter_map(|id| filename2uploaded.get(id).and_then(|uploaded| uploaded.id()))
+            .map(|id| {
+                let mut moved = MovedAsset::from_duplicate(id.clone());
+                moved.set_upload_status(Status::Duplicate);
+                moved
+            });
+
+        if let Ok(album) = Album::get_or_create(client, album_name.to_string()) {
+            if shared && album.created() {
+                if let Err(err) = album
+                    .album()
+                    .set_description(client, SHARED_ALBUM_DESCRIPTION)
+                {
+                    warn!("Failed to mark album {album_name} as shared: {err}");
                 }
+            }
+
+            let uploaded: Vec<&Uploaded> = asset_device_ids
+                .iter()
+                .filter_map(|id| filename2uploaded.get(id).copied())
+                .collect();
+
+            // Assets that already existed on the server might be in the existing album already,
+            // e.g. from a previous import, and don't have to be added again. Newly created
+            // assets can't be in any album yet.
+            let present: HashSet<AssetId> =
+                if album.created() || !uploaded.iter().any(|uploaded| uploaded.existed()) {
+                    HashSet::new()
+                } else {
+                    album
+                        .album()
+                        .asset_ids(client)
+                        .map(|ids| ids.into_iter().collect())
+                        .unwrap_or_default()
+                };
+
+            // Immich Asset IDs of all uploaded assets that are not in the album yet
+            let (present, missing): (Vec<AssetId>, Vec<AssetId>) = uploaded
+                .iter()
+                .filter_map(|uploaded| uploaded.id().cloned())
+                .partition(|id| present.contains(id));
+
+            let added = if missing.is_empty() {
+                Ok(Vec::new())
             } else {
-                // failed to move assets to album, for whatever reason
-                device_ids_to_moved_asset_failure(
-                    &asset_device_ids,
-                    &mut filename2assetid,
-                    &mut moved_assets,
-                );
+                album.album().add_assets(client, missing.into_iter())
+            };
+
+            let moved_assets = match added {
+                Ok(mut moved_assets) => {
+                    moved_assets.extend(present.into_iter().map(MovedAsset::from_duplicate));
+                    let statuses: HashMap<&AssetId, &Status> = uploaded
+                        .iter()
+                        .filter_map(|uploaded| uploaded.id().map(|id| (id, uploaded.status())))
+                        .collect();
+                    for moved in moved_assets.iter_mut() {
+                        if let Some(&status) = moved.id().and_then(|id| statuses.get(id)) {
+                            moved.set_upload_status(status.clone());
+                        }
+                    }
+                    moved_assets
+                }
+                Err(_) => device_ids_to_moved_asset_failure(asset_device_ids, filename2uploaded),
+            };
+            (Some(album), moved_assets.into_iter().chain(left).collect())
+        } else {
+            // failed to move assets to album, for whatever reason
+            let failed = device_ids_to_moved_asset_failure(asset_device_ids, filename2uploaded);
+            (None, failed.into_iter().chain(left).collect())
+        }
+    }
+}
+
+/// Progress of recreating the albums of a Google Takeout import, see [`Uploader::album_progress`]
+#[derive(Clone, Debug)]
+pub struct AlbumProgress {
+    album: String,
+    moved: usize,
+    failed: usize,
+    done: usize,
+    total: usize,
+}
+
+impl AlbumProgress {
+    /// The name of the album that was processed
+    pub fn album(&self) -> &str {
+        &self.album
+    }
+
+    /// The number of assets that are in the album now
+    pub fn moved(&self) -> usize {
+        self.moved
+    }
+
+    /// The number of assets that could not be added to the album
+    pub fn failed(&self) -> usize {
+        self.failed
+    }
+
+    /// The number of albums that were processed so far, including this one
+    pub fn done(&self) -> usize {
+        self.done
+    }
+
+    /// The number of albums that are recreated in total
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+/// The result of a Google Takeout import via [`Uploader::upload`]
+///
+/// Holds the [`MovedAsset`] results of all assets and reports which albums were
+/// newly created and which already existed on the Immich server.
+///
+/// # Examples
+///
+/// ```no_run
+/// use immich::Client;
+/// use immich::takeout::Uploader;
+/// use immich::upload::Uploaded;
+/// use crossbeam_channel::unbounded;
+///
+/// let client = Client::with_email(
+///     "https://immich-web-url/api",
+///     "email@somewhere",
+///     "s3cr3tpassword"
+/// ).unwrap();
+///
+/// let (result_sender, result_receiver) = unbounded::<Uploaded>();
+///
+/// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+/// let imported = takeout.upload(&client, 5, result_sender, |_| true).unwrap();
+///
+/// println!(
+///     "{} albums created, {} already existed",
+///     imported.albums_created(),
+///     imported.albums_existing()
+/// );
+///
+/// for album in imported.albums() {
+///     println!("https://immich-web-url/albums/{}", album.album().id());
+/// }
+/// ```
+pub struct Imported {
+    assets: Vec<MovedAsset>,
+    albums: Vec<GetOrCreate>,
+    summary: ImportSummary,
+}
+
+impl Imported {
+    /// The results of moving the assets into their albums
+    pub fn assets(&self) -> &[MovedAsset] {
+        &self.assets
+    }
+
+    /// All albums that were used during the import
+    ///
+    /// This includes the "Google Takout Import" album, see [`Imported::import_album`].
+    pub fn albums(&self) -> &[GetOrCreate] {
+        &self.albums
+    }
+
+    /// The import album that contains all uploaded assets, "Google Takout Import" by default
+    pub fn import_album(&self) -> Option<&Album> {
+        self.albums.first().map(GetOrCreate::album)
+    }
+
+    /// Accounts for all images and videos of the archive, see [`ImportSummary`]
+    pub fn summary(&self) -> &ImportSummary {
+        &self.summary
+    }
+
+    /// The number of albums that were newly created
+    pub fn albums_created(&self) -> usize {
+        self.albums.iter().filter(|album| album.created()).count()
+    }
+
+    /// The number of albums that already existed on the Immich server
+    pub fn albums_existing(&self) -> usize {
+        self.albums.iter().filter(|album| !album.created()).count()
+    }
+
+    /// The number of [`MovedAsset`] results
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Returns true if no asset was moved to any album
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+}
+
+/// Accounts for all images and videos of a Google Takeout import, see [`Imported::summary`]
+///
+/// Every scanned image and video file is either excluded by the filters of the import
+/// (e.g. [`Uploader::upload_albums`]) or attempted. Every attempted one is either created,
+/// a duplicate, failed or could not be read from the archive. A photo that is in several
+/// folders of the archive is counted once per folder.
+///
+/// # Examples
+///
+/// ```no_run
+/// use immich::Client;
+/// use immich::takeout::Uploader;
+/// use crossbeam_channel::unbounded;
+///
+/// let client = Client::with_email(
+///     "https://immich-web-url/api",
+///     "email@somewhere",
+///     "s3cr3tpassword"
+/// ).unwrap();
+///
+/// let (result_sender, result_receiver) = unbounded();
+///
+/// let mut takeout = Uploader::new("/path/to/takeout/file.tar.gz").unwrap();
+/// let imported = takeout.upload(&client, 5, result_sender, |_| true).unwrap();
+///
+/// println!("{}", imported.summary());
+/// // Scanned: 10000 | Attempted: 10000 | Created: 9790 | Duplicate: 50 | Failure: 10 | Unreadable: 150
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    scanned: usize,
+    attempted: usize,
+    created: usize,
+    duplicate: usize,
+    failure: usize,
+    unreadable: usize,
+}
+
+impl ImportSummary {
+    fn new(scanned: usize, attempted: usize, uploaded: &[Uploaded]) -> Self {
+        let mut summary = Self {
+            scanned,
+            attempted,
+            ..Default::default()
+        };
+        for uploaded in uploaded {
+            match uploaded.status() {
+                Status::Created | Status::Replaced => summary.created += 1,
+                Status::Duplicate => summary.duplicate += 1,
+                Status::Failure => summary.failure += 1,
             }
         }
-        moved_assets
+        summary.unreadable = attempted.saturating_sub(uploaded.len());
+        summary
+    }
+
+    /// The number of image and video files read from the archive
+    ///
+    /// Unlike [`Uploader::len`], a photo that is in several folders is counted once per
+    /// folder, e.g. in `Photos from 2019` and in its album.
+    pub fn scanned(&self) -> usize {
+        self.scanned
+    }
+
+    /// The number of images and videos that were not excluded by the filters of the import
+    pub fn attempted(&self) -> usize {
+        self.attempted
+    }
+
+    /// The number of newly created assets
+    pub fn created(&self) -> usize {
+        self.created
+    }
+
+    /// The number of assets that were already present on the server
+    pub fn duplicate(&self) -> usize {
+        self.duplicate
+    }
+
+    /// The number of assets that failed to upload, including unsupported file types
+    pub fn failure(&self) -> usize {
+        self.failure
+    }
+
+    /// The number of images and videos that could not be read from the archive
+    pub fn unreadable(&self) -> usize {
+        self.unreadable
+    }
+}
+
+impl Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Scanned: {} | Attempted: {} | Created: {} | Duplicate: {} | Failure: {} | Unreadable: {}",
+            self.scanned,
+            self.attempted,
+            self.created,
+            self.duplicate,
+            self.failure,
+            self.unreadable
+        )
+    }
+}
+
+impl IntoIterator for Imported {
+    type IntoIter = IntoIter<MovedAsset>;
+    type Item = MovedAsset;
+    fn into_iter(self) -> Self::IntoIter {
+        self.assets.into_iter()
     }
 }
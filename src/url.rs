@@ -30,6 +30,10 @@ impl TryFrom<String> for Url {
 }
 
 impl Url {
+    pub fn as_str(&self) -> &str {
+        &self.url
+    }
+
     pub fn add_path(&self, path: &str) -> String {
         let url = &self.url;
         if path.starts_with('/') {
@@ -4,15 +4,56 @@ use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
 use time::{
     format_description::BorrowedFormatItem, macros::format_description, Date, OffsetDateTime, Time,
+    UtcOffset,
 };
+use ureq::Agent;
 
+/// The default `deviceId` of uploaded assets
 pub(crate) const CLIENT_NAME: &str = "Immich-0.1 (Rust Client)";
 
+/// Identifies the crate in the User-Agent, it's appended to custom User-Agents
+pub(crate) const USER_AGENT: &str = concat!("immich-rs/", env!("CARGO_PKG_VERSION"));
+
 pub(crate) const DEFAULT_HEADERS: [(&str, &str); 2] =
-    [("Accept", "application/json"), ("User-Agent", CLIENT_NAME)];
+    [("Accept", "application/json"), ("User-Agent", USER_AGENT)];
+
+/// Creates a HTTP agent that uses the TLS backend of the enabled cargo feature
+///
+/// `rustls` is preferred if both the `rustls` and `native-tls` features are enabled.
+/// Without any of them, only plain `http` connections are possible.
+///
+/// Returns [`ImmichError::Tls`] if the TLS library of the system can't be initialized.
+pub(crate) fn agent() -> ImmichResult<Agent> {
+    #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+    {
+        use std::sync::{Arc, OnceLock};
+
+        // Loading the system certificates is expensive, so the connector is shared
+        static CONNECTOR: OnceLock<Arc<native_tls::TlsConnector>> = OnceLock::new();
+        let connector = match CONNECTOR.get() {
+            Some(connector) => connector.clone(),
+            None => {
+                let connector = Arc::new(
+                    native_tls::TlsConnector::new()
+                        .map_err(|err| ImmichError::Tls(err.to_string()))?,
+                );
+                CONNECTOR.get_or_init(|| connector).clone()
+            }
+        };
+        Ok(ureq::builder().tls_connector(connector).build())
+    }
+    #[cfg(not(all(feature = "native-tls", not(feature = "rustls"))))]
+    {
+        Ok(ureq::agent())
+    }
+}
 
 const DATETIME_FORMAT: &[BorrowedFormatItem<'static>] =
-    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].000Z");
+    format_description!("[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z");
+
+const DATETIME_OFFSET_FORMAT: &[BorrowedFormatItem<'static>] = format_description!(
+    "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3][offset_hour sign:mandatory]:[offset_minute]"
+);
 
 const DATETIME_FILENAME_FORMAT: &[BorrowedFormatItem<'static>] =
     format_description!("[year][month][day]_[hour][minute][second]");
@@ -21,9 +62,58 @@ pub(crate) fn serialize_timestamp<S: Serializer>(date: &DateTime, s: S) -> Resul
     s.serialize_str(&date.to_string())
 }
 
-#[derive(Deserialize)]
-/// Wrapper for UTC-based timetstamps used in Immich metadata
-pub struct DateTime(OffsetDateTime);
+/// Converts a base64 encoded checksum, as reported by Immich, to its hex representation
+///
+/// Returns `None` if `encoded` is not valid base64
+pub(crate) fn base64_to_hex(encoded: &str) -> Option<String> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut hex = String::with_capacity(encoded.len() * 3 / 2);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in encoded.trim_end_matches('=').bytes() {
+        let value = ALPHABET.iter().position(|&a| a == c)? as u32;
+        buffer = (buffer << 6) | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            hex.push_str(&format!("{:02x}", (buffer >> bits) & 0xFF));
+        }
+    }
+    Some(hex)
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// Wrapper for timetstamps used in Immich metadata
+///
+/// Timestamps are UTC-based by default, but can use a different UTC offset
+/// (see [`DateTime::with_offset`]).
+///
+/// `DateTime`s are compared by the point in time they represent, independent of their
+/// UTC offset.
+///
+/// # Examples
+///
+/// ```
+/// use immich::{Asset, DateTime};
+///
+/// let mut assets: Vec<Asset> = [2024, 2019, 2022]
+///     .into_iter()
+///     .map(|year| {
+///         Asset::builder()
+///             .created_at(DateTime::new(year, 6, 1, 12, 0, 0).unwrap())
+///             .build()
+///     })
+///     .collect();
+///
+/// // upload the oldest photos first
+/// assets.sort_by(|a, b| a.created_at().cmp(b.created_at()));
+/// assert_eq!(assets[0].created_at().year(), 2019);
+///
+/// let recent = assets.iter().filter(|asset| asset.created_at().year() >= 2022);
+/// assert_eq!(recent.count(), 2);
+/// ```
+pub struct DateTime(#[serde(with = "time::serde::rfc3339")] OffsetDateTime);
 
 impl DateTime {
     pub(crate) fn filename(&self) -> String {
@@ -51,6 +141,89 @@ impl DateTime {
             Time::from_hms(hour, minute, second).map_err(|_| ImmichError::InvalidDate)?,
         )))
     }
+
+    /// Returns the same point in time in the local time of a UTC offset
+    ///
+    /// Immich uses the offset to display the local time at which a photo was taken.
+    /// The sign of `hours` also applies to `minutes`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::DateTime;
+    ///
+    /// let date = DateTime::new(2025, 1, 28, 5, 42, 36).unwrap();
+    /// assert_eq!(date.to_string(), "2025-01-28T05:42:36.000Z");
+    ///
+    /// let local = date.with_offset(-5, -30).unwrap();
+    /// assert_eq!(local.to_string(), "2025-01-28T00:12:36.000-05:30");
+    /// ```
+    pub fn with_offset(&self, hours: i8, minutes: i8) -> ImmichResult<Self> {
+        let offset =
+            UtcOffset::from_hms(hours, minutes, 0).map_err(|_| ImmichError::InvalidDate)?;
+        Ok(self.to_offset(offset))
+    }
+
+    /// Returns the same `DateTime` with the given milliseconds, e.g. for burst photos
+    ///
+    /// Immich stores timestamps with millisecond precision, which keeps the order of
+    /// photos that were taken within the same second.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ImmichError::InvalidDate`] if `millisecond` is `1000` or more
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::DateTime;
+    ///
+    /// let date = DateTime::new(2025, 1, 28, 5, 42, 36).unwrap();
+    /// assert_eq!(date.with_millisecond(250).unwrap().to_string(), "2025-01-28T05:42:36.250Z");
+    /// assert!(date.with_millisecond(1000).is_err());
+    /// ```
+    pub fn with_millisecond(&self, millisecond: u16) -> ImmichResult<Self> {
+        self.0
+            .replace_millisecond(millisecond)
+            .map(Self)
+            .map_err(|_| ImmichError::InvalidDate)
+    }
+
+    pub(crate) fn to_offset(&self, offset: UtcOffset) -> Self {
+        Self(self.0.to_offset(offset))
+    }
+
+    /// The calendar year, in the UTC offset of the `DateTime`
+    pub fn year(&self) -> i32 {
+        self.0.year()
+    }
+
+    /// The month of the year (`1` - `12`), in the UTC offset of the `DateTime`
+    pub fn month(&self) -> u8 {
+        self.0.month().into()
+    }
+
+    /// The day of the month (`1` - `31`), in the UTC offset of the `DateTime`
+    pub fn day(&self) -> u8 {
+        self.0.day()
+    }
+
+    /// Returns the underlying [`OffsetDateTime`] of the `time` crate
+    ///
+    /// Use this for calculations that are not covered by `DateTime` itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::DateTime;
+    ///
+    /// let date = DateTime::new(2025, 1, 28, 5, 42, 36).unwrap();
+    /// let next_week = date.as_offset_datetime() + time::Duration::weeks(1);
+    /// assert_eq!(DateTime::from(next_week).day(), 4);
+    /// ```
+    pub fn as_offset_datetime(&self) -> OffsetDateTime {
+        self.0
+    }
 }
 
 impl Default for DateTime {
@@ -65,9 +238,15 @@ impl Display for DateTime {
         write!(
             f,
             "{}",
-            self.0
-                .format(DATETIME_FORMAT)
-                .expect("OffsetDateTime is formattable using DATETIME_FORMAT")
+            if self.0.offset().is_utc() {
+                self.0
+                    .format(DATETIME_FORMAT)
+                    .expect("OffsetDateTime is formattable using DATETIME_FORMAT")
+            } else {
+                self.0
+                    .format(DATETIME_OFFSET_FORMAT)
+                    .expect("OffsetDateTime is formattable using DATETIME_OFFSET_FORMAT")
+            }
         )
     }
 }
@@ -84,6 +263,12 @@ impl From<OffsetDateTime> for DateTime {
     }
 }
 
+impl From<DateTime> for OffsetDateTime {
+    fn from(time: DateTime) -> Self {
+        time.0
+    }
+}
+
 #[derive(Error, Debug)]
 /// Error types used in this crate
 pub enum ImmichError {
@@ -95,13 +280,29 @@ pub enum ImmichError {
     Status(u16, String),
     #[error("Error connecting: {0}")]
     /// Error during HTTP connection
-    Transport(String),
+    ///
+    /// Contains the kind and message of the error and, if available, the underlying
+    /// transport error as [`source`](std::error::Error::source)
+    Transport(String, #[source] Option<Box<ureq::Transport>>),
     #[error("IO error")]
     /// Error reading from filesystem or input streams
     Io {
         #[from]
         source: std::io::Error,
     },
+    #[error("Unable to read {}: {source}", path.display())]
+    /// Error reading a file from the filesystem
+    ///
+    /// Contains the path of the file, use the [`ErrorKind`](std::io::ErrorKind) of the
+    /// `source` to tell missing files apart from files that can't be read.
+    IoAt {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("Unable to initialize TLS: {0}")]
+    /// The TLS library of the system can't be initialized
+    Tls(String),
     #[error("Invalid URL: {0}")]
     /// The URL used for creating a client is invalid
     InvalidUrl(String),
@@ -119,19 +320,51 @@ pub enum ImmichError {
     InvalidId,
     #[error("Unable to read Takeout archive")]
     InvalidTakeoutArchive,
+    #[error("Takeout archive does not contain any images or videos")]
+    /// The Takeout archive does not contain any recognized media files
+    EmptyTakeoutArchive,
+    #[error("Invalid asset: {0}")]
+    /// The asset does not contain valid media data
+    InvalidAsset(String),
+    #[error("Asset {0} is part of an external library and cannot be modified")]
+    /// The asset belongs to a read-only external library
+    ExternalAsset(String),
+    #[error("Not found: {0}")]
+    /// The album, asset or other object does not exist on the server (anymore),
+    /// or the user has no access to it
+    ///
+    /// Contains the response of the server.
+    NotFound(String),
+}
+
+impl ImmichError {
+    /// Creates the error of an unsuccessful response, missing objects are reported as [`ImmichError::NotFound`]
+    pub(crate) fn from_status(status: u16, body: String) -> Self {
+        // Immich responds with 400 if an album or asset does not exist or is not accessible
+        if status == 404 || (status == 400 && body.contains("Not found or no")) {
+            Self::NotFound(body)
+        } else {
+            Self::Status(status, body)
+        }
+    }
 }
 
 impl From<ureq::Error> for ImmichError {
     fn from(value: ureq::Error) -> Self {
         match value {
             ureq::Error::Status(code, resp) => {
-                ImmichError::Status(code, resp.status_text().to_string())
+                // the body contains the error message of the server, if any
+                let status_text = resp.status_text().to_string();
+                let body = resp.into_string().unwrap_or_default();
+                ImmichError::from_status(code, if body.is_empty() { status_text } else { body })
             }
             ureq::Error::Transport(transport) => ImmichError::Transport(
-                transport
-                    .message()
-                    .unwrap_or("Unknown connection error")
-                    .to_string(),
+                format!(
+                    "{}: {}",
+                    transport.kind(),
+                    transport.message().unwrap_or("Unknown connection error")
+                ),
+                Some(Box::new(transport)),
             ),
         }
     }
@@ -157,17 +390,46 @@ impl From<crate::takeout::ParseError> for ImmichError {
 
 pub type ImmichResult<T> = Result<T, ImmichError>;
 
-#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 #[serde(transparent)]
+/// Unique identifier of objects on the Immich server
+///
+/// The default `Id` is empty and is used for objects that don't exist on the server yet.
 pub struct Id {
     id: String,
 }
 
 impl Id {
+    const NIL: &str = "00000000-0000-0000-0000-000000000000";
+
+    /// The all-zero placeholder id, used e.g. for failed uploads
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use immich::AssetId;
+    ///
+    /// let id = AssetId::nil();
+    /// assert!(id.is_nil());
+    /// assert_eq!(id.to_string(), "00000000-0000-0000-0000-000000000000");
+    /// ```
+    pub fn nil() -> Self {
+        Self {
+            id: Self::NIL.to_string(),
+        }
+    }
+
+    /// Returns true if the id is the all-zero placeholder id
+    pub fn is_nil(&self) -> bool {
+        self.id == Self::NIL
+    }
+
     /// Poor-man's check that the ID is formatted like a UUID
     /// f0edb589-1312-4161-b41e-0a18f127b3dd
+    ///
+    /// The all-zero placeholder id is not a valid id.
     pub(crate) fn is_safe(&self) -> bool {
-        if self.id.len() != 36 {
+        if self.id.len() != 36 || self.is_nil() {
             return false;
         }
         self.id.chars().enumerate().all(|(idx, c)| {
@@ -212,7 +474,7 @@ impl PartialEq<str> for Id {
 }
 
 #[allow(non_snake_case)]
-#[derive(Deserialize)]
+#[derive(Clone, Deserialize)]
 /// The owner of an [`crate::Asset`] on the Immich server
 pub struct User {
     id: Id,
@@ -243,10 +505,101 @@ impl Display for User {
     }
 }
 
+#[derive(Debug, Deserialize)]
+/// Number of images and videos on the Immich server
+pub struct Statistics {
+    images: usize,
+    videos: usize,
+    total: usize,
+}
+
+impl Statistics {
+    /// The number of images
+    pub fn images(&self) -> usize {
+        self.images
+    }
+
+    /// The number of videos
+    pub fn videos(&self) -> usize {
+        self.videos
+    }
+
+    /// The total number of assets
+    pub fn total(&self) -> usize {
+        self.total
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+/// File types that the Immich server accepts, see [`crate::Client::supported_media_types`]
+///
+/// All extensions are lowercase and include the leading dot, e.g. `.jpg`.
+pub struct MediaTypes {
+    #[serde(default)]
+    image: Vec<String>,
+    #[serde(default)]
+    video: Vec<String>,
+    #[serde(default)]
+    sidecar: Vec<String>,
+}
+
+impl MediaTypes {
+    /// The supported image file extensions
+    pub fn images(&self) -> &[String] {
+        &self.image
+    }
+
+    /// The supported video file extensions
+    pub fn videos(&self) -> &[String] {
+        &self.video
+    }
+
+    /// The supported sidecar file extensions, e.g. `.xmp`
+    pub fn sidecars(&self) -> &[String] {
+        &self.sidecar
+    }
+
+    /// Returns `true` if the server accepts images or videos with the extension of `filename`
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use immich::Client;
+    ///
+    /// let client = Client::with_email(
+    ///     "https://immich-web-url/api",
+    ///     "email@somewhere",
+    ///     "s3cr3tpassword"
+    /// ).unwrap();
+    ///
+    /// let media_types = client.supported_media_types().unwrap();
+    /// assert!(media_types.supports("IMG_1234.JPG"));
+    /// ```
+    pub fn supports(&self, filename: &str) -> bool {
+        match std::path::Path::new(filename).extension() {
+            Some(ext) => {
+                let ext = format!(".{}", ext.to_string_lossy().to_lowercase());
+                self.image.contains(&ext) || self.video.contains(&ext)
+            }
+            None => false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn checksum_base64_to_hex() {
+        assert_eq!(
+            base64_to_hex("TLa/w9Q2xpWyMNUMtasdeerzL24=").unwrap(),
+            "4cb6bfc3d436c695b230d50cb5ab1d79eaf32f6e"
+        );
+        assert_eq!(base64_to_hex("").unwrap(), "");
+        assert!(base64_to_hex("not base64!").is_none());
+    }
+
     #[test]
     fn test_safe_uuid() {
         assert!(Id::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").is_ok());
@@ -257,5 +610,95 @@ mod tests {
         assert!(Id::try_from("3fa85f[]-5717-4562-b3fc-2c963f66afa6").is_err());
         assert!(Id::try_from("3fa()f64-5717-4562-b3fc-2c963f66afa6").is_err());
         assert!(Id::try_from("3f..5f64-5717-4562-b3fc-2c963f66afa6").is_err());
+        assert!(Id::try_from("00000000-0000-0000-0000-000000000000").is_err());
+    }
+
+    #[test]
+    fn test_id_hash() {
+        let mut ids = std::collections::HashSet::new();
+        ids.insert(Id::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap());
+        ids.insert(Id::try_from("f0edb589-1312-4161-b41e-0a18f127b3dd").unwrap());
+        ids.insert(Id::nil());
+        assert_eq!(ids.len(), 2);
+    }
+
+    #[test]
+    fn datetime_offset() {
+        let date = DateTime::new(2025, 1, 28, 22, 42, 36).unwrap();
+        assert_eq!(
+            date.with_offset(2, 0).unwrap().to_string(),
+            "2025-01-29T00:42:36.000+02:00"
+        );
+        assert_eq!(
+            date.with_offset(0, 0).unwrap().to_string(),
+            "2025-01-28T22:42:36.000Z"
+        );
+        assert!(date.with_offset(26, 0).is_err());
+
+        let parsed: DateTime = serde_json::from_str(r#""2025-01-29T00:42:36.000+02:00""#).unwrap();
+        assert_eq!(parsed.to_string(), "2025-01-29T00:42:36.000+02:00");
+    }
+
+    #[test]
+    fn datetime_subsecond() {
+        let parsed: DateTime = serde_json::from_str(r#""2025-01-29T00:42:36.125+02:00""#).unwrap();
+        assert_eq!(parsed.to_string(), "2025-01-29T00:42:36.125+02:00");
+
+        let date = DateTime::new(2025, 1, 28, 22, 42, 36).unwrap();
+        let burst = date.with_millisecond(40).unwrap();
+        assert_eq!(burst.to_string(), "2025-01-28T22:42:36.040Z");
+        assert!(date < burst);
+
+        // sub-millisecond precision, e.g. of file timestamps, is truncated
+        let precise = DateTime::from(
+            OffsetDateTime::from_unix_timestamp_nanos(1_738_104_156_123_999_999).unwrap(),
+        );
+        assert_eq!(precise.to_string(), "2025-01-28T22:42:36.123Z");
+    }
+
+    #[test]
+    fn supported_media_types() {
+        let media_types: MediaTypes = serde_json::from_str(
+            r#"{
+                "image": [".avif", ".heic", ".heif", ".jpeg", ".jpg", ".png"],
+                "video": [".3gp", ".mov", ".mp4"],
+                "sidecar": [".xmp"]
+            }"#,
+        )
+        .unwrap();
+        assert!(media_types.supports("IMG_1.jpg"));
+        assert!(media_types.supports("IMG_1.HEIF"));
+        assert!(media_types.supports("Holiday/VID_1.mp4"));
+        assert!(!media_types.supports("IMG_1.jpg.xmp"));
+        assert!(!media_types.supports("notes.txt"));
+        assert!(!media_types.supports("README"));
+        assert_eq!(media_types.sidecars(), [".xmp"]);
+    }
+
+    #[test]
+    fn datetime_ordering() {
+        let date = DateTime::new(2025, 1, 28, 22, 42, 36).unwrap();
+        let local = date.with_offset(2, 0).unwrap();
+        assert_eq!(date, local);
+        assert_eq!(local.day(), 29);
+        assert_eq!(date.day(), 28);
+        assert_eq!(date.month(), 1);
+
+        let later = DateTime::new(2025, 1, 28, 23, 0, 0).unwrap();
+        assert!(local < later);
+        assert_eq!(date.clone().max(later.clone()), later);
+    }
+
+    #[test]
+    fn transport_error_source() {
+        use std::error::Error;
+
+        let client = crate::Client::mock(|_, _| (0, String::new()));
+        let err = client.check_connection().unwrap_err();
+        assert!(matches!(err, ImmichError::Transport(..)));
+        assert!(err
+            .to_string()
+            .starts_with("Error connecting: Network Error"));
+        assert!(err.source().is_some());
     }
 }